@@ -1,3 +1,6 @@
+use std::fmt::{Display, Write};
+
+use crate::libs::matcher::{Match, Matcher};
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -41,6 +44,9 @@ impl Solver for Day {
     fn pt2(&self, input: &str) -> String {
         solve(input, NUMBER_MAPPING_PT_2)
     }
+    fn debug(&self, input: &str) -> Option<String> {
+        Some(debug(input))
+    }
 }
 
 pub(crate) fn input() -> &'static str {
@@ -48,22 +54,103 @@ pub(crate) fn input() -> &'static str {
 }
 
 fn solve(input: &str, string_numbers: &[(&str, u32)]) -> String {
-    input
-        .lines()
-        .map(|line| {
-            let mut numbers: Vec<u32> = vec![];
-            for i in 0..line.len() {
-                let slice = &line[i..];
-                if let Some(&(_k, v)) = string_numbers.iter().find(|(k, _v)| slice.starts_with(k)) {
-                    numbers.push(v);
-                }
-            }
-            numbers.first().unwrap() * 10 + numbers.last().unwrap()
-        })
+    calibrations(input, string_numbers)
+        .map(|calibration| calibration.unwrap().value())
         .sum::<u32>()
         .to_string()
 }
 
+/// The first and last tokens found on one line of the document. Match spans
+/// are byte offsets within `line`, which itself starts at byte `offset` of
+/// the input.
+#[derive(Debug, PartialEq)]
+struct Calibration<'a> {
+    line: &'a str,
+    offset: usize,
+    first: Match<u32>,
+    last: Match<u32>,
+}
+
+impl<'a> Calibration<'a> {
+    fn value(&self) -> u32 {
+        self.first.value * 10 + self.last.value
+    }
+
+    fn token(&self, m: &Match<u32>) -> &'a str {
+        &self.line[m.start..m.end]
+    }
+}
+
+/// Shows the line with the first token marked by `^` and the last by `~`
+/// underneath; a `*` marks bytes shared by both, as in `eightwo`.
+impl<'a> Display for Calibration<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let markers: String = (0..self.line.len())
+            .map(|i| {
+                let first = (self.first.start..self.first.end).contains(&i);
+                let last = (self.last.start..self.last.end).contains(&i);
+                match (first, last) {
+                    (true, true) => '*',
+                    (true, false) => '^',
+                    (false, true) => '~',
+                    (false, false) => ' ',
+                }
+            })
+            .collect();
+        writeln!(
+            f,
+            "{} -> {} ({} + {})",
+            self.line,
+            self.value(),
+            self.token(&self.first),
+            self.token(&self.last)
+        )?;
+        write!(f, "{}", markers.trim_end())
+    }
+}
+
+/// The calibration for every line, or `None` for lines without any token.
+fn calibrations<'a>(
+    input: &'a str,
+    string_numbers: &[(&str, u32)],
+) -> impl Iterator<Item = Option<Calibration<'a>>> {
+    let matcher = Matcher::new(string_numbers);
+    let mut offset = 0;
+    input.split_inclusive('\n').map(move |raw| {
+        let line = raw.trim_end_matches(['\r', '\n']);
+        let line_offset = offset;
+        offset += raw.len();
+        Some(Calibration {
+            line,
+            offset: line_offset,
+            first: matcher.first(line)?,
+            last: matcher.last(line)?,
+        })
+    })
+}
+
+/// Every line of `input` with its matches highlighted, for tracking down a
+/// miscalculated calibration value.
+fn debug(input: &str) -> String {
+    let mut out = String::new();
+    for (i, calibration) in calibrations(input, NUMBER_MAPPING_PT_2).enumerate() {
+        match calibration {
+            Some(calibration) => {
+                for (j, row) in calibration.to_string().lines().enumerate() {
+                    let label = if j == 0 {
+                        format!("{:>4}:", i + 1)
+                    } else {
+                        String::from("     ")
+                    };
+                    writeln!(out, "{label} {row}").unwrap();
+                }
+            }
+            None => writeln!(out, "{:>4}: no digits found", i + 1).unwrap(),
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,6 +186,63 @@ zoneight234
         assert_eq!("281".to_string(), Day {}.pt2(example_input_pt2()))
     }
 
+    #[test]
+    fn test_pt2_overlapping_words() {
+        assert_eq!("82".to_string(), Day {}.pt2("eightwo"));
+        assert_eq!("18".to_string(), Day {}.pt2("oneight"));
+    }
+
+    #[test]
+    fn test_calibrations() {
+        let calibrations: Vec<_> = calibrations(example_input_pt2(), NUMBER_MAPPING_PT_2)
+            .map(Option::unwrap)
+            .collect();
+        assert_eq!(7, calibrations.len());
+        let xtwone = &calibrations[3];
+        assert_eq!(
+            (
+                "xtwone3four",
+                38,
+                Match {
+                    start: 1,
+                    end: 4,
+                    value: 2
+                },
+                Match {
+                    start: 7,
+                    end: 11,
+                    value: 4
+                }
+            ),
+            (xtwone.line, xtwone.offset, xtwone.first, xtwone.last)
+        );
+        assert_eq!(
+            ("two", "four"),
+            (xtwone.token(&xtwone.first), xtwone.token(&xtwone.last))
+        );
+        assert_eq!(24, xtwone.value());
+        assert_eq!(
+            vec![29, 83, 13, 24, 42, 14, 76],
+            calibrations
+                .iter()
+                .map(Calibration::value)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(
+            "   1: eightwo -> 82 (eight + two)
+      ^^^^*~~
+   2: no digits found
+   3: 7pqrstsixteen -> 76 (7 + six)
+      ^     ~~~
+",
+            debug("eightwo\nabc\n7pqrstsixteen")
+        );
+    }
+
     #[test]
     fn test_pt2() {
         assert_eq!("54925".to_string(), Day {}.pt2(input()))
@@ -1,95 +1,281 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
 use std::str::FromStr;
 
+use crate::libs::parse::{self, Scanner};
 use crate::problem::Solver;
 
 pub struct Day {}
 
+const BAG: &str = "12 red, 13 green, 14 blue";
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
+const LIKELY_BAG_LIMIT: u32 = 30;
+
 impl Solver for Day {
     fn pt1(&self, input: &str) -> String {
+        let bag: Bag = BAG.parse().unwrap();
         solve(input)
-            .iter()
-            .filter(|game| game.possible())
+            .possible_with(&bag)
             .map(|game| game.name)
             .sum::<u32>()
             .to_string()
     }
     fn pt2(&self, input: &str) -> String {
         solve(input)
+            .0
             .iter()
-            .map(Game::minimum)
-            .map(|minimums| minimums.0 * minimums.1 * minimums.2)
+            .map(|game| game.minimum().power(&COLOURS))
             .sum::<u32>()
             .to_string()
     }
+    fn debug(&self, input: &str) -> Option<String> {
+        let games = solve(input);
+        let mut labels = vec!["given", "minimum"];
+        let mut bags: Vec<Bag> = vec![BAG.parse().unwrap(), games.minimum()];
+        if let Some((bag, _)) = games.most_likely_bag(LIKELY_BAG_LIMIT) {
+            labels.push("most likely");
+            bags.push(bag);
+        }
+        let possible_for_all = games.bags_for_all(&bags);
+        let mut out = String::new();
+        for (label, bag) in labels.iter().zip(&bags) {
+            let possible = possible_for_all.contains(&bag);
+            let likelihood = games.log_likelihood(bag);
+            writeln!(
+                out,
+                "{label:>11}: {bag} (all possible: {possible}, log-likelihood: {likelihood:.2})"
+            )
+            .unwrap();
+        }
+        if let Some(best) = games.best_explained_by(&bags) {
+            writeln!(out, "best explained by {best}").unwrap();
+        }
+        Some(out)
+    }
 }
 
 pub(crate) fn input() -> &'static str {
     include_str!("day02-input.txt").trim()
 }
 
+/// Cube counts by colour; colours that are not mentioned count as zero.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Cubes(BTreeMap<String, u32>);
+
+type Round = Cubes;
+type Bag = Cubes;
+
+impl Cubes {
+    fn get(&self, colour: &str) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+    fn add(&mut self, colour: &str, count: u32) {
+        *self.0.entry(String::from(colour)).or_insert(0) += count;
+    }
+    fn fits_in(&self, bag: &Bag) -> bool {
+        self.0
+            .iter()
+            .all(|(colour, &count)| count <= bag.get(colour))
+    }
+    fn max(&self, other: &Cubes) -> Cubes {
+        let mut max = self.clone();
+        for (colour, &count) in &other.0 {
+            let entry = max.0.entry(colour.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+        max
+    }
+    fn power(&self, colours: &[&str]) -> u32 {
+        colours.iter().map(|colour| self.get(colour)).product()
+    }
+    fn total(&self) -> u32 {
+        self.0.values().sum()
+    }
+    fn log_probability(&self, bag: &Bag) -> f64 {
+        if !self.fits_in(bag) {
+            return f64::NEG_INFINITY;
+        }
+        self.0
+            .iter()
+            .map(|(colour, &count)| ln_choose(bag.get(colour), count))
+            .sum::<f64>()
+            - ln_choose(bag.total(), self.total())
+    }
+}
+
+fn ln_choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    (0..k)
+        .map(|i| f64::from(n - i).ln() - f64::from(i + 1).ln())
+        .sum()
+}
+
+impl Display for Cubes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = self
+            .0
+            .iter()
+            .map(|(colour, count)| format!("{count} {colour}"))
+            .collect();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+impl FromStr for Cubes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(s);
+        let cubes = parse_cubes(&mut scanner)?;
+        scanner.end()?;
+        Ok(cubes)
+    }
+}
+
+fn parse_cubes(scanner: &mut Scanner) -> parse::Result<Cubes> {
+    let mut cubes = Cubes::default();
+    loop {
+        let count = scanner.skip_whitespace().number()?;
+        scanner.tag(" ")?;
+        cubes.add(scanner.word()?, count);
+        if scanner.tag(",").is_err() {
+            return Ok(cubes);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
 struct Game {
     name: u32,
     rounds: Vec<Round>,
 }
 
 impl Game {
-    fn possible(&self) -> bool {
-        self.rounds.iter().all(Round::possible)
+    fn possible(&self, bag: &Bag) -> bool {
+        self.rounds.iter().all(|round| round.fits_in(bag))
     }
-    fn minimum(&self) -> Round {
-        self.rounds.iter().fold(Round(0, 0, 0), |acc, e| {
-            Round(e.0.max(acc.0), e.1.max(acc.1), e.2.max(acc.2))
-        })
+    fn minimum(&self) -> Bag {
+        self.rounds
+            .iter()
+            .fold(Bag::default(), |acc, round| acc.max(round))
+    }
+    /// How well `bag` explains the game, as a log-likelihood: each round
+    /// draws its cubes without replacement and puts them back afterwards.
+    fn log_likelihood(&self, bag: &Bag) -> f64 {
+        self.rounds
+            .iter()
+            .map(|round| round.log_probability(bag))
+            .sum()
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct Round(u32, u32, u32);
-
-impl FromStr for Round {
+impl FromStr for Game {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(s.split(',').fold(Round(0, 0, 0), |acc, e| {
-            let (num, color) = e.trim().split_once(' ').unwrap();
-            let num: u32 = num.parse().unwrap();
-            match color {
-                "red" => acc.add_red(num),
-                "green" => acc.add_green(num),
-                "blue" => acc.add_blue(num),
-                &_ => panic!("Unknown color {color}"),
-            }
-        }))
+        Ok(parse_game(s)?)
     }
 }
 
-impl Round {
-    fn add_red(&self, red: u32) -> Self {
-        Round(self.0 + red, self.1, self.2)
+fn parse_game(line: &str) -> parse::Result<Game> {
+    let mut scanner = Scanner::new(line);
+    scanner.tag("Game ")?;
+    let name = scanner.number()?;
+    scanner.tag(":")?;
+    let mut rounds = vec![parse_cubes(&mut scanner)?];
+    while scanner.tag(";").is_ok() {
+        rounds.push(parse_cubes(&mut scanner)?);
     }
-    fn add_green(&self, green: u32) -> Self {
-        Round(self.0, self.1 + green, self.2)
+    scanner.end()?;
+    Ok(Game { name, rounds })
+}
+
+struct Games(Vec<Game>);
+
+impl Games {
+    fn possible_with<'a>(&'a self, bag: &'a Bag) -> impl Iterator<Item = &'a Game> {
+        self.0.iter().filter(|game| game.possible(bag))
     }
-    fn add_blue(&self, blue: u32) -> Self {
-        Round(self.0, self.1, self.2 + blue)
+    fn bags_for_all<'a>(&self, bags: &'a [Bag]) -> Vec<&'a Bag> {
+        bags.iter()
+            .filter(|bag| self.0.iter().all(|game| game.possible(bag)))
+            .collect()
     }
-    fn possible(&self) -> bool {
-        self.0 <= 12 && self.1 <= 13 && self.2 <= 14
+    fn minimum(&self) -> Bag {
+        self.0
+            .iter()
+            .fold(Bag::default(), |acc, game| acc.max(&game.minimum()))
     }
-}
+    fn log_likelihood(&self, bag: &Bag) -> f64 {
+        self.0.iter().map(|game| game.log_likelihood(bag)).sum()
+    }
+    fn best_explained_by<'a>(&self, bags: &'a [Bag]) -> Option<&'a Bag> {
+        bags.iter()
+            .map(|bag| (bag, self.log_likelihood(bag)))
+            .filter(|(_, score)| score.is_finite())
+            .fold(None, |best: Option<(&Bag, f64)>, (bag, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((bag, score)),
+            })
+            .map(|(bag, _)| bag)
+    }
+    /// The most likely bag holding at most `limit` cubes of each colour that
+    /// was seen, with its log-likelihood. Ties go to the smallest bag.
+    ///
+    /// The likelihood can keep growing with the bag size, so the search is
+    /// bounded; `None` when even the minimum bag exceeds `limit`.
+    fn most_likely_bag(&self, limit: u32) -> Option<(Bag, f64)> {
+        let minimum = self.minimum();
+        let colours: Vec<&String> = minimum.0.keys().collect();
+        let mut counts: Vec<u32> = minimum.0.values().copied().collect();
+        if counts.iter().any(|&count| count > limit) {
+            return None;
+        }
 
-fn solve(input: &str) -> Vec<Game> {
-    input
-        .lines()
-        .map(|line| {
-            let (name, rounds) = line.split_once(": ").unwrap();
-            let rounds = rounds.split("; ").map(|r| r.parse::<Round>().unwrap());
-            Game {
-                name: name.replace("Game ", "").parse::<u32>().unwrap(),
-                rounds: rounds.collect(),
+        let mut best: Option<(Bag, f64)> = None;
+        loop {
+            let bag = Cubes(
+                colours
+                    .iter()
+                    .map(|&colour| colour.clone())
+                    .zip(counts.iter().copied())
+                    .collect(),
+            );
+            let score = self.log_likelihood(&bag);
+            let better = match &best {
+                Some((best_bag, best_score)) => {
+                    score > *best_score || (score == *best_score && bag.total() < best_bag.total())
+                }
+                None => true,
+            };
+            if better {
+                best = Some((bag, score));
             }
-        })
-        .collect()
+
+            // Count through every combination, like an odometer.
+            let Some(i) = counts.iter().position(|&count| count < limit) else {
+                return best;
+            };
+            for (j, colour) in colours.iter().enumerate().take(i) {
+                counts[j] = minimum.get(colour);
+            }
+            counts[i] += 1;
+        }
+    }
+}
+
+impl FromStr for Games {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Games(parse::lines(s, parse_game)?))
+    }
+}
+
+fn solve(input: &str) -> Games {
+    input.parse().unwrap()
 }
 
 #[cfg(test)]
@@ -104,9 +290,123 @@ Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"
     }
 
+    fn cubes(counts: &[(&str, u32)]) -> Cubes {
+        Cubes(
+            counts
+                .iter()
+                .map(|&(colour, count)| (String::from(colour), count))
+                .collect(),
+        )
+    }
+
     #[test]
     fn test_round() {
-        assert_eq!(Round(4, 0, 3), " 3 blue, 4 red".parse().unwrap())
+        assert_eq!(
+            cubes(&[("red", 4), ("blue", 3)]),
+            " 3 blue, 4 red".parse().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_any_colour() {
+        let game: Game = "Game 7: 2 teal, 1 red; 5 teal".parse().unwrap();
+        assert_eq!(cubes(&[("red", 1), ("teal", 5)]), game.minimum());
+        assert_eq!(5, game.minimum().power(&["red", "teal"]));
+        assert_eq!(0, game.minimum().power(&COLOURS));
+        assert!(game.possible(&"5 teal, 1 red".parse().unwrap()));
+        assert!(!game.possible(&"4 teal, 1 red".parse().unwrap()));
+        assert!(!game.possible(&BAG.parse().unwrap()));
+    }
+
+    #[test]
+    fn test_bags_for_all() {
+        let games: Games = example_input().parse().unwrap();
+        let bags: Vec<Bag> = [
+            BAG,
+            "20 red, 13 green, 15 blue",
+            "20 red, 12 green, 15 blue",
+        ]
+        .iter()
+        .map(|bag| bag.parse().unwrap())
+        .collect();
+        assert_eq!(vec![&bags[1]], games.bags_for_all(&bags));
+        assert_eq!(
+            cubes(&[("red", 20), ("green", 13), ("blue", 15)]),
+            games.minimum()
+        );
+    }
+
+    #[test]
+    fn test_log_probability() {
+        let round: Round = "1 red, 1 blue".parse().unwrap();
+        let bag: Bag = "3 red, 1 blue".parse().unwrap();
+        // 3 of the 6 ways to draw two cubes pick the blue one.
+        assert!((round.log_probability(&bag) - 0.5_f64.ln()).abs() < 1e-9);
+        assert_eq!(
+            0.0,
+            round.log_probability(&"1 red, 1 blue".parse().unwrap())
+        );
+        assert_eq!(
+            f64::NEG_INFINITY,
+            round.log_probability(&"3 red".parse().unwrap())
+        );
+        assert_eq!(
+            f64::NEG_INFINITY,
+            round.log_probability(&"1 red".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_most_likely_bag() {
+        let games: Games = "Game 1: 1 red, 1 blue; 2 red".parse().unwrap();
+        let (bag, score) = games.most_likely_bag(10).unwrap();
+        assert_eq!(cubes(&[("red", 3), ("blue", 1)]), bag);
+        assert!((score - 0.25_f64.ln()).abs() < 1e-9);
+        assert_eq!(None, games.most_likely_bag(1));
+
+        let games: Games = "Game 1: 3 red; 2 red".parse().unwrap();
+        assert_eq!(
+            (cubes(&[("red", 3)]), 0.0),
+            games.most_likely_bag(10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_best_explained_by() {
+        let games: Games = example_input().parse().unwrap();
+        let bags: Vec<Bag> = [
+            BAG,
+            "20 red, 13 green, 15 blue",
+            "40 red, 26 green, 30 blue",
+        ]
+        .iter()
+        .map(|bag| bag.parse().unwrap())
+        .collect();
+        assert_eq!(f64::NEG_INFINITY, games.log_likelihood(&bags[0]));
+        // Game 3 draws all 20 red cubes at once, which is unlikely unless
+        // there are more of them in the bag.
+        assert!(games.log_likelihood(&bags[1]) < games.log_likelihood(&bags[2]));
+        assert_eq!(Some(&bags[2]), games.best_explained_by(&bags));
+        assert_eq!(None, games.best_explained_by(&bags[..1]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(String::from("1:17: expected a number")),
+            "Game 1: 3 blue; red".parse::<Game>().map(|_| ())
+        );
+        assert_eq!(
+            Err(String::from("1:15: unexpected ' 4 red'")),
+            "Game 1: 3 blue 4 red".parse::<Game>().map(|_| ())
+        );
+        assert_eq!(
+            Err(String::from(
+                "2:9: invalid value '-1': invalid digit found in string"
+            )),
+            "Game 1: 1 red\nGame 2: -1 red".parse::<Games>().map(|_| ())
+        );
+        assert!("3 blue, 4".parse::<Bag>().is_err());
     }
 
     #[test]
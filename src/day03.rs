@@ -1,13 +1,51 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::libs::{Coordinate, Grid};
 use crate::problem::Solver;
 
 pub struct Day {}
 
 impl Solver for Day {
     fn pt1(&self, input: &str) -> String {
-        format!("{:?}", GearRatios::from(input).part_numbers().sum::<u32>())
+        format!(
+            "{:?}",
+            Schematic::from(input)
+                .part_numbers()
+                .map(|n| n.value)
+                .sum::<u32>()
+        )
     }
     fn pt2(&self, input: &str) -> String {
-        format!("{:?}", GearRatios::from(input).gear_ratios().sum::<u32>())
+        format!("{:?}", Schematic::from(input).gear_ratios().sum::<u32>())
+    }
+    fn debug(&self, input: &str) -> Option<String> {
+        let schematic = Schematic::from(input);
+        let mut out = String::new();
+        for number in schematic.orphans() {
+            writeln!(
+                out,
+                "no symbol next to {} at row {}, columns {}..{}",
+                number.value, number.row, number.start, number.end
+            )
+            .unwrap();
+        }
+        let glyphs: BTreeSet<u8> = schematic.symbols.iter().map(|s| s.glyph).collect();
+        for glyph in glyphs {
+            let numbers: Vec<u32> = schematic
+                .numbers_next_to_glyph(glyph)
+                .map(|n| n.value)
+                .collect();
+            writeln!(
+                out,
+                "'{}': {} numbers, summing to {}",
+                char::from(glyph),
+                numbers.len(),
+                numbers.iter().sum::<u32>()
+            )
+            .unwrap();
+        }
+        Some(out)
     }
 }
 
@@ -15,86 +53,140 @@ pub(crate) fn input() -> &'static str {
     include_str!("day03-input.txt").trim()
 }
 
-struct GearRatios<'a>(&'a str);
+/// A number in the schematic, covering columns `start..end` of `row`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PartNumber {
+    value: u32,
+    row: usize,
+    start: usize,
+    end: usize,
+}
 
-impl<'a> From<&'a str> for GearRatios<'a> {
-    fn from(value: &'a str) -> Self {
-        Self(value)
-    }
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Symbol {
+    glyph: u8,
+    position: Coordinate<usize>,
 }
 
-impl<'a> GearRatios<'a> {
-    fn part_numbers(&self) -> impl Iterator<Item = u32> {
-        let numbers = self.numbers(|c| c != '.' && !c.is_numeric());
-        numbers
-            .into_iter()
-            .reduce(|acc, e| [acc, e].concat())
-            .unwrap()
-            .into_iter()
-            .map(|e| e.2)
-    }
+/// The parsed engine schematic. Numbers and symbols are linked when a digit
+/// of the number touches the symbol, diagonals included.
+struct Schematic {
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+    /// For every symbol, the indices of the numbers next to it.
+    symbol_numbers: Vec<Vec<usize>>,
+    /// For every number, the indices of the symbols next to it.
+    number_symbols: Vec<Vec<usize>>,
+}
 
-    fn gear_ratios(&self) -> impl Iterator<Item = u32> {
-        let numbers = self.numbers(|c| c == '*');
-        numbers
-            .into_iter()
-            .filter(|a| a.len() > 1)
-            .map(|t| t.iter().map(|t| t.2).reduce(|acc, e| acc * e).unwrap())
-    }
+impl From<&str> for Schematic {
+    fn from(value: &str) -> Self {
+        let grid = Grid::from(value);
+        let is_symbol = |c: u8| c != b'.' && !c.is_ascii_digit();
 
-    fn symbols(&self, predicate: fn(char) -> bool) -> Vec<(usize, usize, char)> {
-        self.0
-            .lines()
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.chars()
-                    .enumerate()
-                    .filter_map(|(x, c)| if predicate(c) { Some((x, y, c)) } else { None })
-                    .collect::<Vec<_>>()
-            })
-            .collect()
-    }
-
-    fn numbers(&self, adjacent_to: fn(char) -> bool) -> Vec<Vec<(usize, usize, u32)>> {
-        let mut numbers: Vec<Vec<(usize, usize, u32)>> = vec![];
-        for adjacent_to in self.symbols(adjacent_to) {
-            let mut my_numbers: Vec<(usize, usize, u32)> = vec![];
-            let mut y = if adjacent_to.1 > 0 {
-                adjacent_to.1 - 1
-            } else {
-                adjacent_to.1
-            };
-
-            let lines = &self.0.lines().collect::<Vec<&str>>();
-            while y <= adjacent_to.1 + 1 && y < lines.len() {
-                let line = lines[y];
-                let mut x = if adjacent_to.0 > 0 {
-                    adjacent_to.0 - 1
-                } else {
-                    adjacent_to.0
-                };
-                while x <= adjacent_to.0 + 1 && x < line.len() {
-                    if line[x..].starts_with(|c: char| c.is_numeric()) {
-                        while x > 0 && line[x - 1..].starts_with(|c: char| c.is_numeric()) {
-                            x -= 1;
-                        }
-                        let mut end = x;
-                        while line[end..].starts_with(|c: char| c.is_numeric()) {
-                            end += 1;
-                        }
-                        my_numbers.push((x, y, line[x..end].parse().unwrap()));
-                        x = end + 1;
-                    } else {
-                        x += 1;
-                    }
-                }
-                y += 1;
+        let mut symbols = vec![];
+        let mut symbol_at = Grid::new(grid.width, grid.height, None);
+        for (i, &glyph) in grid.points.iter().enumerate() {
+            if is_symbol(glyph) {
+                let position = grid.coord(i);
+                symbol_at[&position] = Some(symbols.len());
+                symbols.push(Symbol { glyph, position });
             }
-            if !my_numbers.is_empty() {
-                numbers.push(my_numbers);
+        }
+
+        let mut numbers = vec![];
+        for row in 0..grid.height {
+            let line = &grid.points[row * grid.width..(row + 1) * grid.width];
+            let mut x = 0;
+            while x < line.len() {
+                let digits = line[x..].iter().take_while(|c| c.is_ascii_digit()).count();
+                if digits == 0 {
+                    x += 1;
+                    continue;
+                }
+                let value = line[x..x + digits]
+                    .iter()
+                    .fold(0, |acc, &d| acc * 10 + u32::from(d - b'0'));
+                numbers.push(PartNumber {
+                    value,
+                    row,
+                    start: x,
+                    end: x + digits,
+                });
+                x += digits;
             }
         }
-        numbers
+
+        let mut symbol_numbers = vec![vec![]; symbols.len()];
+        let number_symbols: Vec<Vec<usize>> = numbers
+            .iter()
+            .enumerate()
+            .map(|(n, number)| {
+                let rows = number.row.saturating_sub(1)..=(number.row + 1).min(grid.height - 1);
+                let columns = number.start.saturating_sub(1)..=number.end.min(grid.width - 1);
+                let adjacent: Vec<usize> = rows
+                    .flat_map(|y| columns.clone().map(move |x| Coordinate(x, y)))
+                    .filter_map(|c| symbol_at[&c])
+                    .collect();
+                adjacent.iter().for_each(|&s| symbol_numbers[s].push(n));
+                adjacent
+            })
+            .collect();
+
+        Self {
+            numbers,
+            symbols,
+            symbol_numbers,
+            number_symbols,
+        }
+    }
+}
+
+impl Schematic {
+    /// Numbers next to at least one symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// Numbers not next to any symbol.
+    fn orphans(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, symbols)| symbols.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// Numbers next to the symbol with index `symbol`.
+    fn numbers_next_to(&self, symbol: usize) -> impl Iterator<Item = &PartNumber> {
+        self.symbol_numbers[symbol]
+            .iter()
+            .map(|&n| &self.numbers[n])
+    }
+
+    /// Numbers next to any symbol drawn as `glyph`, each listed once.
+    fn numbers_next_to_glyph(&self, glyph: u8) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(move |(_, symbols)| symbols.iter().any(|&s| self.symbols[s].glyph == glyph))
+            .map(|(number, _)| number)
+    }
+
+    /// Indices of the symbols drawn as `glyph` that touch exactly `count` numbers.
+    fn symbols_next_to_exactly(&self, glyph: u8, count: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len()).filter(move |&s| {
+            self.symbols[s].glyph == glyph && self.symbol_numbers[s].len() == count
+        })
+    }
+
+    fn gear_ratios(&self) -> impl Iterator<Item = u32> + '_ {
+        self.symbols_next_to_exactly(b'*', 2)
+            .map(|gear| self.numbers_next_to(gear).map(|n| n.value).product())
     }
 }
 
@@ -120,6 +212,52 @@ mod tests {
         assert_eq!("4361".to_string(), Day {}.pt1(example_input()))
     }
 
+    #[test]
+    fn test_schematic() {
+        let schematic = Schematic::from(example_input());
+        assert_eq!(10, schematic.numbers.len());
+        assert_eq!(6, schematic.symbols.len());
+        assert_eq!(
+            PartNumber {
+                value: 633,
+                row: 2,
+                start: 6,
+                end: 9
+            },
+            schematic.numbers[3]
+        );
+        assert_eq!(
+            Symbol {
+                glyph: b'#',
+                position: Coordinate(6, 3)
+            },
+            schematic.symbols[1]
+        );
+        assert_eq!(
+            vec![114, 58],
+            schematic.orphans().map(|n| n.value).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![633],
+            schematic
+                .numbers_next_to_glyph(b'#')
+                .map(|n| n.value)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![2],
+            schematic
+                .symbols_next_to_exactly(b'*', 1)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 5],
+            schematic
+                .symbols_next_to_exactly(b'*', 2)
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_pt1() {
         assert_eq!("539590".to_string(), Day {}.pt1(input()))
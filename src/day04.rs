@@ -1,89 +1,161 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+use crate::libs::parse::{self, ParseError, Scanner};
 use crate::problem::Solver;
 
 pub struct Day {}
 
 impl Solver for Day {
     fn pt1(&self, input: &str) -> String {
-        format!("{}", Game::from(input).score())
+        format!("{}", input.parse::<Game>().unwrap().score())
     }
     fn pt2(&self, input: &str) -> String {
-        format!("{}", Game::from(input).cards())
+        format!("{}", input.parse::<Game>().unwrap().cards())
+    }
+    fn debug(&self, input: &str) -> Option<String> {
+        let game = input.parse::<Game>().unwrap();
+        let mut out = String::new();
+        for (card, copies) in game.0.iter().zip(game.copies()) {
+            writeln!(out, "{}, held {copies} times", card.breakdown()).unwrap();
+        }
+        Some(out)
     }
 }
 
-struct Game<'a>(&'a str);
-impl<'a> From<&'a str> for Game<'a> {
-    fn from(value: &'a str) -> Self {
-        Self(value)
+struct Game(Vec<ScratchCard>);
+impl FromStr for Game {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(parse::lines(s, parse_card)?))
     }
 }
-impl<'a> Game<'a> {
+impl Game {
     fn score(&self) -> u32 {
+        self.0.iter().map(ScratchCard::score).sum()
+    }
+    fn cards(&self) -> u64 {
+        self.copies().iter().sum()
+    }
+    /// How many copies of each card end up being held, originals included.
+    ///
+    /// Every copy of a card wins one copy of each of the next cards, so the
+    /// wins are kept in a difference array: a card adds its count where its
+    /// range of won cards starts and removes it again where the range ends.
+    fn copies(&self) -> Vec<u64> {
+        let mut difference = vec![0_i64; self.0.len() + 1];
+        let mut won = 0_i64;
         self.0
-            .lines()
-            .map(ScratchCard::from)
-            .map(|g| g.score())
-            .sum()
-    }
-    fn cards(&self) -> u32 {
-        let mut cards: Vec<(u32, ScratchCard)> = self
-            .0
-            .lines()
-            .map(ScratchCard::from)
-            .map(|o| (1, o))
-            .collect();
-        (0..cards.len()).for_each(|i| {
-            let card = &cards[i];
-            let score = card.1.matching_numbers();
-
-            for _ in 0..card.0 {
-                ((i + 1)..=((i + score as usize).min(cards.len()))).for_each(|j| {
-                    cards[j].0 += 1;
-                });
-            }
-        });
-        cards.iter().map(|t| t.0).sum::<u32>()
+            .iter()
+            .enumerate()
+            .map(|(i, card)| {
+                won += difference[i];
+                let copies = 1 + won;
+                let end = (i + 1 + card.matching_numbers()).min(self.0.len());
+                difference[(i + 1).min(end)] += copies;
+                difference[end] -= copies;
+                u64::try_from(copies).unwrap()
+            })
+            .collect()
     }
 }
 
-#[derive(Debug)]
-struct ScratchCard<'a>(&'a str);
-impl<'a> From<&'a str> for ScratchCard<'a> {
-    fn from(value: &'a str) -> Self {
-        Self(value)
-    }
+#[derive(Debug, PartialEq)]
+struct ScratchCard {
+    id: u32,
+    winning: BTreeSet<u32>,
+    held: BTreeSet<u32>,
 }
 
-impl<'a> ScratchCard<'a> {
-    fn matching_numbers(&self) -> u32 {
-        let (winning, my) = self.numbers();
-        let num_matches: u32 = my
-            .iter()
-            .filter(|my_number| winning.contains(my_number))
-            .count()
-            .try_into()
-            .unwrap();
-        num_matches
+impl ScratchCard {
+    /// The held numbers that are also winning numbers, in ascending order.
+    fn matching(&self) -> impl Iterator<Item = u32> + '_ {
+        self.winning.intersection(&self.held).copied()
+    }
+    fn matching_numbers(&self) -> usize {
+        self.matching().count()
     }
     fn score(&self) -> u32 {
         match self.matching_numbers() {
             0 => 0,
-            n => 2_u32.pow(n - 1),
+            n => 1 << (n - 1),
+        }
+    }
+    fn breakdown(&self) -> Breakdown {
+        Breakdown {
+            id: self.id,
+            matching: self.matching().collect(),
+            points: self.score(),
         }
     }
-    fn numbers(&self) -> (Vec<u32>, Vec<u32>) {
-        let values = self.0.split_once(':').unwrap().1.trim();
-        let (winning_numbers, my_numbers) = values.split_once('|').unwrap();
-        let winning_numbers: Vec<u32> = winning_numbers
-            .split_ascii_whitespace()
-            .map(|num| num.parse().unwrap())
-            .collect();
-        let my_numbers: Vec<u32> = my_numbers
-            .split_ascii_whitespace()
-            .map(|num| num.parse().unwrap())
-            .collect();
-        (winning_numbers, my_numbers)
+}
+
+/// Why a card is worth what it is: the first match scores one point and
+/// every further match doubles it.
+#[derive(Debug, PartialEq)]
+struct Breakdown {
+    id: u32,
+    matching: Vec<u32>,
+    points: u32,
+}
+
+impl Display for Breakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let matching: Vec<String> = self.matching.iter().map(u32::to_string).collect();
+        write!(
+            f,
+            "Card {}: {} matching ({}) = {} points",
+            self.id,
+            self.matching.len(),
+            matching.join(", "),
+            self.points
+        )
+    }
+}
+
+impl FromStr for ScratchCard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse_card(s)?)
+    }
+}
+
+fn parse_card(line: &str) -> parse::Result<ScratchCard> {
+    let mut scanner = Scanner::new(line);
+    scanner.tag("Card")?;
+    let column = scanner.column();
+    if scanner.skip_whitespace().column() == column {
+        return Err(scanner.error("expected a space after 'Card'"));
+    }
+    let id = scanner.number()?;
+    scanner.tag(":")?;
+    let winning = parse_numbers(&mut scanner)?;
+    scanner.skip_whitespace().tag("|")?;
+    let held = parse_numbers(&mut scanner)?;
+    scanner.end()?;
+    Ok(ScratchCard { id, winning, held })
+}
+
+fn parse_numbers(scanner: &mut Scanner) -> parse::Result<BTreeSet<u32>> {
+    let mut numbers = BTreeSet::new();
+    while scanner
+        .skip_whitespace()
+        .rest()
+        .starts_with(|c: char| c.is_ascii_digit())
+    {
+        let column = scanner.column();
+        let number = scanner.number()?;
+        if !numbers.insert(number) {
+            return Err(ParseError::new(
+                column,
+                format!("duplicate number {number}"),
+            ));
+        }
     }
+    Ok(numbers)
 }
 
 pub(crate) fn input() -> &'static str {
@@ -113,11 +185,71 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
         assert_eq!("22897".to_string(), Day {}.pt1(input()))
     }
 
+    #[test]
+    fn test_scratch_card() {
+        let card: ScratchCard = example_input().lines().next().unwrap().parse().unwrap();
+        assert_eq!(1, card.id);
+        assert_eq!(BTreeSet::from([17, 41, 48, 83, 86]), card.winning);
+        assert_eq!(vec![17, 48, 83, 86], card.matching().collect::<Vec<_>>());
+        assert_eq!(
+            Breakdown {
+                id: 1,
+                matching: vec![17, 48, 83, 86],
+                points: 8
+            },
+            card.breakdown()
+        );
+        assert_eq!(
+            "Card 1: 4 matching (17, 48, 83, 86) = 8 points",
+            card.breakdown().to_string()
+        );
+    }
+
+    #[test]
+    fn test_scratch_card_errors() {
+        assert_eq!(
+            Err(String::from("1:15: duplicate number 41")),
+            "Card 1: 41 48 41 | 83".parse::<ScratchCard>()
+        );
+        assert_eq!(
+            Err(String::from("1:1: expected 'Card'")),
+            "Cart 1: 41 | 83".parse::<ScratchCard>()
+        );
+        assert_eq!(
+            Err(String::from("1:5: expected a space after 'Card'")),
+            "Card1: 41 | 83".parse::<ScratchCard>()
+        );
+        assert_eq!(
+            Err(String::from("1:7: expected ':'")),
+            "Card 1 41 | 83".parse::<ScratchCard>()
+        );
+        assert_eq!(
+            Err(String::from("2:15: duplicate number 2")),
+            "Card 1: 1 | 1\nCard 2: 2 | 2 2".parse::<Game>().map(|_| ())
+        );
+    }
+
     #[test]
     fn test_pt2_example() {
         assert_eq!("30".to_string(), Day {}.pt2(example_input()))
     }
 
+    #[test]
+    fn test_copies() {
+        assert_eq!(
+            vec![1, 2, 4, 8, 14, 1],
+            example_input().parse::<Game>().unwrap().copies()
+        );
+        // The last card's wins run past the end of the table.
+        assert_eq!(
+            vec![1, 2],
+            "Card 1: 1 | 1\nCard 2: 2 | 2"
+                .parse::<Game>()
+                .unwrap()
+                .copies()
+        );
+    }
+
     #[test]
     fn test_pt2() {
         assert_eq!("5095824".to_string(), Day {}.pt2(input()))
@@ -1,5 +1,8 @@
-use std::{ops::Range, str::FromStr};
+use std::fmt::{Display, Write};
+use std::str::FromStr;
 
+use crate::libs::interval::{IntervalMap, IntervalSet};
+use crate::libs::parse::{self, ParseError, Scanner};
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -13,59 +16,156 @@ impl Solver for Day {
         let soil_location = input.parse::<Almanac>().unwrap().map(true);
         format!("{soil_location}")
     }
+    fn debug(&self, input: &str) -> Option<String> {
+        let almanac = input.parse::<Almanac>().unwrap();
+        let composed = almanac.composed();
+        let mut out = format!("{composed}\n");
+        let lowest = almanac.map(false);
+        match composed.invert() {
+            Some(inverse) => writeln!(out, "location {lowest} <- seed {}", inverse.get(lowest)),
+            None => writeln!(out, "the composed map cannot be inverted"),
+        }
+        .unwrap();
+        let lowest = IntervalSet::from(lowest..lowest + 1);
+        if let Some(seeds) = almanac.seeds_for("location", &lowest) {
+            writeln!(out, "seeds reaching it: {:?}", seeds.ranges()).unwrap();
+        }
+        for (name, gaps) in almanac.gaps() {
+            writeln!(out, "{name} passes through {:?}", gaps.ranges()).unwrap();
+        }
+        Some(out)
+    }
 }
 
 pub(crate) fn input() -> &'static str {
     include_str!("day05-input.txt").trim()
 }
 
+/// A piecewise-linear function: every range of `ranges` is moved by its
+/// offset and everything else maps to itself.
 #[derive(Debug, PartialEq)]
 struct Map {
     name: String,
-    ranges: Vec<(Range<i64>, i64)>,
+    ranges: IntervalMap<i64, i64>,
 }
 
 impl Map {
-    fn process_seeds(&self, seeds: &[Range<i64>]) -> Vec<Range<i64>> {
-        seeds
-            .iter()
-            .map(|seed| self.process_seed(seed))
-            .reduce(|acc, e| [acc, e].concat())
-            .unwrap()
+    fn process_seeds(&self, seeds: &IntervalSet<i64>) -> IntervalSet<i64> {
+        self.ranges.translate(seeds)
     }
-    fn process_seed(&self, seed: &Range<i64>) -> Vec<Range<i64>> {
-        let mut seeds: Vec<(Range<i64>, i64)> = vec![];
-        let mut ranges = self
-            .ranges
-            .iter()
-            .filter(|range| {
-                range.0.contains(&seed.start) && !(range.0.start..seed.start).is_empty()
-                    || range.0.contains(&seed.end) && !(range.0.start..seed.end).is_empty()
-                    || seed.contains(&range.0.start) && !(seed.start..range.0.start).is_empty()
-                    || seed.contains(&range.0.end) && !(range.0.end..seed.start).is_empty()
-            })
-            .collect::<Vec<&(Range<i64>, i64)>>();
-        ranges.sort_by(|a, b| a.0.start.cmp(&b.0.start));
-        if ranges.is_empty() {
-            seeds.push((seed.clone(), 0));
-        } else {
-            for range in &ranges {
-                let intersection = seed.start.max(range.0.start)..seed.end.min(range.0.end);
-                seeds.push((intersection, range.1));
+    fn get(&self, value: i64) -> i64 {
+        value + self.ranges.get(&value).copied().unwrap_or(0)
+    }
+    fn source(&self) -> &str {
+        self.name
+            .split_once("-to-")
+            .map_or(&self.name, |(source, _)| source)
+    }
+    fn destination(&self) -> &str {
+        self.name
+            .split_once("-to-")
+            .map_or(&self.name, |(_, destination)| destination)
+    }
+    /// Drops ranges with a zero offset; they behave like unmapped values.
+    /// Adjacent ranges with the same offset are already merged on insert.
+    fn simplify(&self) -> Map {
+        Map {
+            name: self.name.clone(),
+            ranges: self
+                .ranges
+                .iter()
+                .filter(|(_, &offset)| offset != 0)
+                .map(|(range, &offset)| (range.clone(), offset))
+                .collect(),
+        }
+    }
+    /// The single map that applies `self` and then `next`.
+    fn compose(&self, next: &Map) -> Map {
+        let mut ranges = vec![];
+        for (range, &offset) in self.ranges.iter() {
+            let image = IntervalSet::from(range.start + offset..range.end + offset);
+            for (next_range, &next_offset) in next.ranges.iter() {
+                let overlap = image.intersection(&IntervalSet::from(next_range.clone()));
+                for part in overlap.ranges() {
+                    ranges.push((part.start - offset..part.end - offset, offset + next_offset));
+                }
             }
-            if seed.start < ranges.first().unwrap().0.start {
-                let before = seed.start..ranges.first().unwrap().0.start;
-                seeds.push((before, 0));
+            for part in image.difference(&next.ranges.domain()).ranges() {
+                ranges.push((part.start - offset..part.end - offset, offset));
             }
-            if seed.end > seeds.last().unwrap().0.end {
-                let after = seeds.last().unwrap().0.end..seed.end;
-                seeds.push((after, 0));
+        }
+        // Values that `self` leaves alone only see `next`.
+        let unmapped = self.ranges.domain();
+        for (next_range, &next_offset) in next.ranges.iter() {
+            for part in IntervalSet::from(next_range.clone())
+                .difference(&unmapped)
+                .ranges()
+            {
+                ranges.push((part.clone(), next_offset));
             }
         }
-        seeds
-            .into_iter()
-            .map(|(range, offset)| (range.start + offset)..(range.end + offset))
-            .collect()
+
+        Map {
+            name: format!("{}-to-{}", self.source(), next.destination()),
+            ranges: ranges.into_iter().collect(),
+        }
+        .simplify()
+    }
+    /// The values that map into `set`.
+    fn preimage(&self, set: &IntervalSet<i64>) -> IntervalSet<i64> {
+        let mut preimage = set.difference(&self.ranges.domain());
+        for (range, &offset) in self.ranges.iter() {
+            let image = IntervalSet::from(range.start + offset..range.end + offset);
+            preimage = preimage.union(&set.intersection(&image).shift(-offset));
+        }
+        preimage
+    }
+    /// The values from zero up to the end of the last range that pass
+    /// through unchanged because no range covers them.
+    fn gaps(&self) -> IntervalSet<i64> {
+        let domain = self.ranges.domain();
+        let end = domain.max().map_or(0, |max| max + 1);
+        IntervalSet::from(0..end).difference(&domain)
+    }
+    /// The map going the other way, or `None` when `self` is not a bijection,
+    /// i.e. when the moved ranges do not exactly cover the ranges they left.
+    fn invert(&self) -> Option<Map> {
+        let images: Vec<_> = self
+            .ranges
+            .iter()
+            .map(|(range, &offset)| (range.start + offset..range.end + offset, -offset))
+            .collect();
+        let covered: IntervalSet<i64> = images.iter().map(|(range, _)| range.clone()).collect();
+        let domain = self.ranges.domain();
+        // Equal total lengths rule out images overlapping one another.
+        let length: i64 = images
+            .iter()
+            .map(|(range, _)| range.end - range.start)
+            .sum();
+        if covered != domain || length != domain.len() {
+            return None;
+        }
+        Some(Map {
+            name: format!("{}-to-{}", self.destination(), self.source()),
+            ranges: images.into_iter().collect(),
+        })
+    }
+}
+
+/// The map in the almanac's own format, one range per line in source order.
+impl Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} map:", self.name)?;
+        for (range, offset) in self.ranges.iter() {
+            write!(
+                f,
+                "\n{} {} {}",
+                range.start + offset,
+                range.start,
+                range.end - range.start
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -76,7 +176,7 @@ struct Almanac {
 }
 
 impl Almanac {
-    fn seeds(&self, seeds_as_range: bool) -> Vec<Range<i64>> {
+    fn seeds(&self, seeds_as_range: bool) -> IntervalSet<i64> {
         if seeds_as_range {
             self.seeds
                 .chunks(2)
@@ -87,38 +187,82 @@ impl Almanac {
         }
     }
     fn map(&self, seeds_as_range: bool) -> i64 {
-        self.maps
-            .iter()
-            .fold(self.seeds(seeds_as_range), |seeds, map| {
-                map.process_seeds(&seeds)
-            })
-            .iter()
-            .map(|range| range.start)
+        self.composed()
+            .process_seeds(&self.seeds(seeds_as_range))
             .min()
             .unwrap()
     }
+    /// The seeds whose `category` values fall in `values`, following the
+    /// maps backwards; `None` for an unknown category.
+    fn seeds_for(&self, category: &str, values: &IntervalSet<i64>) -> Option<IntervalSet<i64>> {
+        let end = if category == "seed" {
+            0
+        } else {
+            1 + self
+                .maps
+                .iter()
+                .position(|map| map.destination() == category)?
+        };
+        Some(
+            self.maps[..end]
+                .iter()
+                .rev()
+                .fold(values.clone(), |values, map| map.preimage(&values)),
+        )
+    }
+    /// The identity gaps of every map, by map name.
+    fn gaps(&self) -> Vec<(&str, IntervalSet<i64>)> {
+        self.maps
+            .iter()
+            .map(|map| (map.name.as_str(), map.gaps()))
+            .collect()
+    }
+    /// All maps composed into a single seed-to-location map.
+    fn composed(&self) -> Map {
+        let (first, rest) = self.maps.split_first().unwrap();
+        rest.iter()
+            .fold(first.simplify(), |map, next| map.compose(next))
+    }
 }
 
 impl FromStr for Map {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines = s.lines().collect::<Vec<&str>>();
-        let (name, map) = lines.split_first().unwrap();
-        let name = name.replace(" map:", "");
-        let ranges: Vec<(Range<i64>, i64)> = map
-            .iter()
-            .map(|m| {
-                let numbers: Vec<i64> = m
-                    .split_ascii_whitespace()
-                    .map(|n| n.parse::<i64>().unwrap())
-                    .collect();
-                let (destination, source_start, source_len) = (numbers[0], numbers[1], numbers[2]);
+        let (header, map) = s.split_once('\n').unwrap_or((s, ""));
+        let mut header = Scanner::new(header);
+        let name = String::from(header.until(" map:")?);
+        header.end()?;
+        let entries = parse::lines(map, |line| match parse::ints::<i64>(line)?[..] {
+            [destination, source_start, source_len] => {
                 let source_end = source_start + source_len;
                 let offset: i64 = destination - source_start;
-                (source_start..source_end, offset)
-            })
-            .collect();
+                Ok((source_start..source_end, offset))
+            }
+            _ => Err(ParseError::new(1, "expected three numbers")),
+        })
+        .map_err(|e| {
+            let line = e.line + 1;
+            e.on_line(line)
+        })?;
+
+        // Inserting overwrites overlapping ranges, so overlaps are caught first.
+        let mut sources = IntervalSet::new();
+        for (i, (range, _)) in entries.iter().enumerate() {
+            if !sources
+                .intersection(&IntervalSet::from(range.clone()))
+                .is_empty()
+            {
+                let message = format!(
+                    "source range {}..{} overlaps an earlier range",
+                    range.start, range.end
+                );
+                return Err(ParseError::new(1, message).on_line(i + 2).into());
+            }
+            sources.insert(range.clone());
+        }
+
+        let ranges: IntervalMap<i64, i64> = entries.into_iter().collect();
         Ok(Map { name, ranges })
     }
 }
@@ -127,14 +271,13 @@ impl FromStr for Almanac {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let maps: Vec<&str> = s.split("\n\n").collect();
+        let maps = parse::sections(s);
         let (seeds, maps) = maps.split_first().unwrap();
-        let seeds = seeds
-            .replace("seeds: ", "")
-            .split_ascii_whitespace()
-            .map(|n| n.parse().unwrap())
-            .collect();
-        let maps: Vec<Map> = maps.iter().map(|map| map.parse::<Map>().unwrap()).collect();
+        let seeds = parse::ints(seeds)?;
+        let maps: Vec<Map> = maps
+            .iter()
+            .map(|map| map.parse::<Map>())
+            .collect::<Result<_, _>>()?;
         Ok(Almanac { seeds, maps })
     }
 }
@@ -184,7 +327,7 @@ humidity-to-location map:
         assert_eq!(
             Map {
                 name: String::from("seed-to-soil"),
-                ranges: vec![((98..100), -48), ((50..98), 2)]
+                ranges: [((98..100), -48), ((50..98), 2)].into_iter().collect()
             },
             "seed-to-soil map:
 50 98 2
@@ -195,14 +338,140 @@ humidity-to-location map:
     }
 
     #[test]
-    fn test_map_process_seed() {
+    fn test_map_process_seeds() {
         assert_eq!(
-            vec![81..82],
+            IntervalSet::from(81..82),
             Map {
                 name: String::from("seed-to-soil"),
-                ranges: vec![((98..100), -48), ((50..98), 2)]
+                ranges: [((98..100), -48), ((50..98), 2)].into_iter().collect()
             }
-            .process_seed(&(79..80))
+            .process_seeds(&IntervalSet::from(79..80))
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let almanac: Almanac = example_input().parse().unwrap();
+        let composed = almanac.composed();
+        assert_eq!("seed-to-location", composed.name);
+        for seed in -10..120 {
+            let expected = almanac.maps.iter().fold(seed, |value, map| map.get(value));
+            assert_eq!(expected, composed.get(seed), "seed: {seed}");
+        }
+        assert_eq!(
+            vec![82, 43, 86, 35],
+            [79, 14, 55, 13].map(|seed| composed.get(seed))
+        );
+    }
+
+    #[test]
+    fn test_simplify() {
+        let map = Map {
+            name: String::from("a-to-b"),
+            ranges: [(0..2, 0), (2..4, 1), (4..6, 1)].into_iter().collect(),
+        };
+        assert_eq!(
+            Map {
+                name: String::from("a-to-b"),
+                ranges: [(2..6, 1)].into_iter().collect()
+            },
+            map.simplify()
+        );
+    }
+
+    #[test]
+    fn test_invert() {
+        let almanac: Almanac = example_input().parse().unwrap();
+        let inverse = almanac.composed().invert().unwrap();
+        assert_eq!("location-to-seed", inverse.name);
+        for seed in -10..120 {
+            assert_eq!(seed, inverse.get(almanac.composed().get(seed)));
+        }
+        // Which seed ends up at the lowest location of part two?
+        assert_eq!(82, inverse.get(46));
+
+        let squashed = Map {
+            name: String::from("a-to-b"),
+            ranges: [(0..2, 5)].into_iter().collect(),
+        };
+        assert_eq!(None, squashed.invert());
+    }
+
+    #[test]
+    fn test_display() {
+        let map: Map = "seed-to-soil map:\n50 98 2\n52 50 48".parse().unwrap();
+        assert_eq!("seed-to-soil map:\n52 50 48\n50 98 2", map.to_string());
+        assert_eq!(map, map.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn test_seeds_for() {
+        let almanac: Almanac = example_input().parse().unwrap();
+        assert_eq!(
+            Some(IntervalSet::from(82..83)),
+            almanac.seeds_for("location", &IntervalSet::from(46..47))
+        );
+        // Soil 81 comes from seed 79, soil 50..52 from seeds 98..100.
+        assert_eq!(
+            Some([79..80, 98..100].into_iter().collect()),
+            almanac.seeds_for("soil", &[81..82, 50..52].into_iter().collect())
+        );
+        assert_eq!(
+            Some(IntervalSet::from(3..5)),
+            almanac.seeds_for("seed", &IntervalSet::from(3..5))
+        );
+        assert_eq!(None, almanac.seeds_for("flux", &IntervalSet::from(3..5)));
+
+        let seeds = almanac.seeds(true);
+        let locations = almanac.composed().process_seeds(&seeds);
+        assert_eq!(
+            Some(seeds),
+            almanac
+                .seeds_for("location", &locations)
+                .map(|s| s.intersection(&almanac.seeds(true)))
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        let almanac: Almanac = example_input().parse().unwrap();
+        let gaps = almanac.gaps();
+        assert_eq!(
+            ("seed-to-soil", IntervalSet::from(0..50)),
+            (gaps[0].0, gaps[0].1.clone())
+        );
+        assert_eq!(
+            ("water-to-light", IntervalSet::from(0..18)),
+            (gaps[3].0, gaps[3].1.clone())
+        );
+        assert_eq!(
+            ("light-to-temperature", IntervalSet::from(0..45)),
+            (gaps[4].0, gaps[4].1.clone())
+        );
+        assert_eq!(
+            ("temperature-to-humidity", IntervalSet::new()),
+            (gaps[5].0, gaps[5].1.clone())
+        );
+    }
+
+    #[test]
+    fn test_overlapping_sources() {
+        assert_eq!(
+            Err(String::from(
+                "3:1: source range 60..70 overlaps an earlier range"
+            )),
+            "seed-to-soil map:\n52 50 48\n0 60 10".parse::<Map>()
+        );
+        assert!("seed-to-soil map:\n52 50 48\n0 98 10"
+            .parse::<Map>()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_map_header() {
+        assert_eq!(
+            Err(String::from("1:1: expected ' map:'")),
+            "seed-to-soil\n52 50 48".parse::<Map>()
         );
     }
 
@@ -1,5 +1,8 @@
-use std::{ops::RangeInclusive, str::FromStr};
+use std::{fmt::Write, ops::RangeInclusive, str::FromStr};
 
+use num::{integer::Roots, BigUint, Integer};
+
+use crate::libs::parse::{self, ParseError};
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -9,16 +12,32 @@ impl Solver for Day {
         format!("{}", input.parse::<Races>().unwrap().margin_of_error())
     }
     fn pt2(&self, input: &str) -> String {
-        format!(
-            "{}",
-            input
-                .parse::<Race>()
-                .unwrap()
-                .winning_button_presses()
-                .size_hint()
-                .1
-                .unwrap()
+        format!("{}", input.parse::<Race>().unwrap().ways_to_win())
+    }
+    fn debug(&self, input: &str) -> Option<String> {
+        let races = input.parse::<Races>().unwrap();
+        let mut out = String::new();
+        strategies(&mut out, "linear", &races, &Linear);
+        strategies(&mut out, "squared", &races, &Accelerating { exponent: 2 });
+        strategies(&mut out, "capped at 20", &races, &Capped { max_speed: 20 });
+        Some(out)
+    }
+}
+
+fn strategies(out: &mut String, name: &str, races: &Races, physics: &impl Physics) {
+    writeln!(out, "{name}:").unwrap();
+    for (race, strategy) in races.0.iter().zip(races.optimal_holds(physics)) {
+        writeln!(
+            out,
+            "  {} ms, record {} mm: hold {} ms for {} mm ({:+}), {} ways to win",
+            race.time,
+            race.distance,
+            strategy.hold,
+            strategy.distance,
+            strategy.margin,
+            race.ways_to_win_with(physics)
         )
+        .unwrap();
     }
 }
 
@@ -28,34 +47,161 @@ pub(crate) fn input() -> &'static str {
 
 #[derive(Debug, PartialEq)]
 struct Race {
-    time: u64,
-    distance: u64,
+    time: u128,
+    distance: u128,
 }
 
 impl Race {
-    fn winning_button_presses(&self) -> RangeInclusive<u64> {
-        let shortest_press = ((self.time / self.distance)..self.time).find(|time_pushed| {
-            let time_remaining = self.time - time_pushed;
-            let speed = time_pushed;
-            let distance = speed * time_remaining;
-            distance > self.distance
-        });
-        match shortest_press {
-            Some(n) => n..=(self.time - n),
-            None => 0..=0,
+    fn winning_button_presses(&self) -> Option<RangeInclusive<u128>> {
+        Linear.winning_holds(self)
+    }
+    fn ways_to_win(&self) -> u128 {
+        self.winning_button_presses()
+            .map_or(0, |presses| presses.end() - presses.start() + 1)
+    }
+    fn ways_to_win_with(&self, physics: &impl Physics) -> u128 {
+        physics
+            .winning_holds(self)
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
+    fn distance_curve<'a>(&'a self, physics: &'a impl Physics) -> impl Iterator<Item = u128> + 'a {
+        (0..=self.time).map(|hold| physics.distance(hold, self.time))
+    }
+    fn optimal_hold(&self, physics: &impl Physics) -> Strategy {
+        let (hold, distance) = physics.optimal_hold(self);
+        let signed = |mm: u128| i128::try_from(mm).expect("distance should fit in i128");
+        Strategy {
+            hold,
+            distance,
+            margin: signed(distance) - signed(self.distance),
+        }
+    }
+}
+
+/// The best hold time for a race, and by how much it beats the record;
+/// a negative margin means the race cannot be won.
+#[derive(Debug, PartialEq)]
+struct Strategy {
+    hold: u128,
+    distance: u128,
+    margin: i128,
+}
+
+trait Physics {
+    fn distance(&self, hold: u128, time: u128) -> u128;
+
+    /// The hold times that beat the record. By default every hold is tried,
+    /// assuming the winners form a single run as they do for any curve that
+    /// rises and then falls.
+    fn winning_holds(&self, race: &Race) -> Option<RangeInclusive<u128>> {
+        let mut wins =
+            (0..=race.time).filter(|&hold| self.distance(hold, race.time) > race.distance);
+        let first = wins.next()?;
+        Some(first..=wins.last().unwrap_or(first))
+    }
+
+    /// The shortest hold that goes the furthest, and how far it goes. By
+    /// default every hold is tried.
+    fn optimal_hold(&self, race: &Race) -> (u128, u128)
+    where
+        Self: Sized,
+    {
+        race.distance_curve(self)
+            .zip(0..)
+            .fold((0, 0), |best, (distance, hold)| {
+                if distance > best.1 {
+                    (hold, distance)
+                } else {
+                    best
+                }
+            })
+    }
+}
+
+struct Linear;
+
+impl Physics for Linear {
+    fn distance(&self, hold: u128, time: u128) -> u128 {
+        hold.saturating_mul(time - hold)
+    }
+
+    fn winning_holds(&self, race: &Race) -> Option<RangeInclusive<u128>> {
+        // Squaring the time overflows u128 for races longer than u64 allows.
+        winning_holds(&BigUint::from(race.time), &BigUint::from(race.distance)).map(|holds| {
+            u128::try_from(holds.start()).unwrap()..=u128::try_from(holds.end()).unwrap()
+        })
+    }
+
+    fn optimal_hold(&self, race: &Race) -> (u128, u128) {
+        // The distance is a downward parabola with its vertex at half the time.
+        let hold = race.time / 2;
+        (hold, self.distance(hold, race.time))
+    }
+}
+
+struct Accelerating {
+    exponent: u32,
+}
+
+impl Physics for Accelerating {
+    fn distance(&self, hold: u128, time: u128) -> u128 {
+        hold.saturating_pow(self.exponent)
+            .saturating_mul(time - hold)
+    }
+}
+
+struct Capped {
+    max_speed: u128,
+}
+
+impl Physics for Capped {
+    fn distance(&self, hold: u128, time: u128) -> u128 {
+        hold.min(self.max_speed).saturating_mul(time - hold)
+    }
+}
+
+/// The hold times `h` with `h * (time - h) > distance`, or `None` if there
+/// are none.
+///
+/// They lie strictly between the roots `(time ± √(time² - 4 distance)) / 2`.
+/// The integer square root is exact, so only a step or two is needed to
+/// settle on the first winning hold, however large the numbers are.
+fn winning_holds<T: Clone + Integer + Roots>(time: &T, distance: &T) -> Option<RangeInclusive<T>> {
+    let two = T::one() + T::one();
+    let square = time.clone() * time.clone();
+    let bound = two.clone() * two.clone() * distance.clone();
+    if square <= bound {
+        return None;
+    }
+    let root = (square - bound).sqrt();
+
+    let wins = |hold: &T| hold.clone() * (time.clone() - hold.clone()) > *distance;
+    let fastest = time.clone() / two.clone();
+    let mut shortest = (time.clone() - root) / two;
+    while shortest > T::zero() && wins(&(shortest.clone() - T::one())) {
+        shortest = shortest - T::one();
+    }
+    while !wins(&shortest) {
+        if shortest >= fastest {
+            return None;
         }
+        shortest = shortest + T::one();
     }
+    Some(shortest.clone()..=(time.clone() - shortest))
 }
 
 #[derive(Debug, PartialEq)]
 struct Races(Vec<Race>);
 
 impl Races {
-    fn margin_of_error(&self) -> usize {
+    fn margin_of_error(&self) -> u128 {
+        self.0.iter().map(Race::ways_to_win).product::<u128>()
+    }
+    fn optimal_holds(&self, physics: &impl Physics) -> Vec<Strategy> {
         self.0
             .iter()
-            .map(|race| race.winning_button_presses().size_hint().1.unwrap())
-            .product::<usize>()
+            .map(|race| race.optimal_hold(physics))
+            .collect()
     }
 }
 
@@ -63,26 +209,18 @@ impl FromStr for Races {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines = s
-            .lines()
-            .map(|line| {
-                let values = line
-                    .split_ascii_whitespace()
-                    .skip(1)
-                    .map(|v| v.parse::<u64>().unwrap())
-                    .collect();
-                values
-            })
-            .collect::<Vec<Vec<u64>>>();
-        let (times, distances) = (lines[0].clone(), lines[1].clone());
+        let lines = parse::lines(s, parse::ints::<u128>)?;
+        let [times, distances] = &lines[..] else {
+            return Err(String::from("Could not parse!"));
+        };
         if times.len() != distances.len() || times.is_empty() {
             Err(String::from("Could not parse!"))
         } else {
             Ok(Races(
                 times
-                    .into_iter()
+                    .iter()
                     .zip(distances)
-                    .map(|(time, distance)| Race { time, distance })
+                    .map(|(&time, &distance)| Race { time, distance })
                     .collect(),
             ))
         }
@@ -93,19 +231,23 @@ impl FromStr for Race {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers: Vec<u64> = s
-            .lines()
-            .map(|line| line.replace(|c: char| !c.is_numeric(), ""))
-            .map(|line| line.parse().unwrap())
-            .collect();
-        let time = numbers[0];
-        let distance = numbers[1];
+        let numbers = parse::lines(s, |line| {
+            let digits = line.replace(|c: char| !c.is_ascii_digit(), "");
+            digits
+                .parse::<u128>()
+                .map_err(|e| ParseError::new(1, format!("invalid value '{digits}': {e}")))
+        })?;
+        let [time, distance] = numbers[..] else {
+            return Err(String::from("expected a time and a distance"));
+        };
         Ok(Race { time, distance })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use num::BigUint;
+
     use super::*;
 
     fn example_input() -> &'static str {
@@ -148,7 +290,7 @@ Distance:  9  40  200"
     #[test]
     fn test_race_fwinning_button_presses() {
         assert_eq!(
-            2..=5,
+            Some(2..=5),
             Race {
                 time: 7,
                 distance: 9
@@ -157,6 +299,174 @@ Distance:  9  40  200"
         )
     }
 
+    #[test]
+    fn test_winning_holds_brute_force() {
+        for time in 0..60_u64 {
+            for distance in 0..(time * time / 4 + 3) {
+                let wins: Vec<u64> = (0..=time)
+                    .filter(|hold| hold * (time - hold) > distance)
+                    .collect();
+                let expected = wins.first().map(|&first| first..=*wins.last().unwrap());
+                assert_eq!(
+                    expected,
+                    winning_holds(&time, &distance),
+                    "time: {time}, distance: {distance}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unwinnable_race() {
+        // The best hold of 1 or 2 only ties the record.
+        let race = Race {
+            time: 3,
+            distance: 2,
+        };
+        assert_eq!(None, race.winning_button_presses());
+        assert_eq!(0, race.ways_to_win());
+        assert_eq!(
+            0,
+            Races(vec![
+                race,
+                Race {
+                    time: 7,
+                    distance: 9
+                }
+            ])
+            .margin_of_error()
+        );
+    }
+
+    #[test]
+    fn test_huge_race() {
+        let time: BigUint = "1000000000000000000000000000000".parse().unwrap();
+        let distance: BigUint = "123456789012345678901234567890".parse().unwrap();
+        let holds = winning_holds(&time, &distance).unwrap();
+        let distance_for = |hold: &BigUint| hold * (&time - hold);
+        let one = BigUint::from(1_u8);
+        assert!(distance_for(holds.start()) > distance);
+        assert!(distance_for(&(holds.start() - &one)) <= distance);
+        assert!(distance_for(holds.end()) > distance);
+        assert!(distance_for(&(holds.end() + &one)) <= distance);
+
+        let race = Race {
+            time: u128::MAX,
+            distance: u128::MAX,
+        };
+        assert_eq!(Some(2..=u128::MAX - 2), race.winning_button_presses());
+    }
+
+    #[test]
+    fn test_race_longer_than_u64() {
+        // Kerned, the time is 10^20, beyond u64::MAX.
+        let input = "Time:      10000000000 0000000000\nDistance:  1";
+        assert_eq!("99999999999999999999".to_string(), Day {}.pt2(input));
+    }
+
+    #[test]
+    fn test_race_parse_errors() {
+        assert_eq!(
+            Err(String::from(
+                "1:1: invalid value '': cannot parse integer from empty string"
+            )),
+            "Time: x\nDistance: 9".parse::<Race>()
+        );
+        assert!(format!("Time: {}\nDistance: 9", "9".repeat(40))
+            .parse::<Race>()
+            .is_err());
+        assert_eq!(
+            Err(String::from("expected a time and a distance")),
+            "Time: 7".parse::<Race>()
+        );
+    }
+
+    #[test]
+    fn test_distance_curve() {
+        let race = Race {
+            time: 7,
+            distance: 9,
+        };
+        assert_eq!(
+            vec![0, 6, 10, 12, 12, 10, 6, 0],
+            race.distance_curve(&Linear).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 6, 10, 8, 6, 4, 2, 0],
+            race.distance_curve(&Capped { max_speed: 2 })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 6, 20, 36, 48, 50, 36, 0],
+            race.distance_curve(&Accelerating { exponent: 2 })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// Linear physics without its closed forms, so every hold is scanned.
+    struct Scanned;
+    impl Physics for Scanned {
+        fn distance(&self, hold: u128, time: u128) -> u128 {
+            Linear.distance(hold, time)
+        }
+    }
+
+    #[test]
+    fn test_optimal_hold() {
+        let races: Races = example_input().parse().unwrap();
+        assert_eq!(
+            vec![
+                Strategy {
+                    hold: 3,
+                    distance: 12,
+                    margin: 3
+                },
+                Strategy {
+                    hold: 7,
+                    distance: 56,
+                    margin: 16
+                },
+                Strategy {
+                    hold: 15,
+                    distance: 225,
+                    margin: 25
+                },
+            ],
+            races.optimal_holds(&Linear)
+        );
+        assert_eq!(
+            Strategy {
+                hold: 2,
+                distance: 10,
+                margin: 1
+            },
+            races.0[0].optimal_hold(&Capped { max_speed: 2 })
+        );
+        assert_eq!(
+            -26,
+            races.0[1].optimal_hold(&Capped { max_speed: 1 }).margin
+        );
+        for race in &races.0 {
+            assert_eq!(race.optimal_hold(&Scanned), race.optimal_hold(&Linear));
+        }
+    }
+
+    #[test]
+    fn test_ways_to_win_with() {
+        let races: Races = example_input().parse().unwrap();
+        for race in &races.0 {
+            assert_eq!(race.ways_to_win(), race.ways_to_win_with(&Scanned));
+        }
+        assert_eq!(
+            5,
+            races.0[0].ways_to_win_with(&Accelerating { exponent: 2 })
+        );
+        assert_eq!(
+            Some(2..=2),
+            Capped { max_speed: 2 }.winning_holds(&races.0[0])
+        );
+    }
+
     #[test]
     fn test_pt1_example() {
         assert_eq!("288".to_string(), Day {}.pt1(example_input()))
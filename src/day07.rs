@@ -1,4 +1,6 @@
 use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
 
 use crate::problem::Solver;
 
@@ -10,7 +12,11 @@ struct Hands<'a>(Vec<Hand<'a>>);
 #[derive(Clone, Debug, PartialEq)]
 struct Hand<'a>(&'a str, u64);
 
-#[derive(Debug, PartialEq)]
+/// A hand type as the sizes of its groups of equal cards, largest first:
+/// a full house is `[3, 2]`.
+type Shape = Vec<usize>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Type {
     FiveOfKind = 6,
     FourOfKind = 5,
@@ -21,109 +27,225 @@ enum Type {
     HighCard = 0,
 }
 
+const TYPES: [Type; 7] = [
+    Type::FiveOfKind,
+    Type::FourOfKind,
+    Type::FullHouse,
+    Type::ThreeOfKind,
+    Type::TwoPair,
+    Type::OnePair,
+    Type::HighCard,
+];
+
+impl Type {
+    fn shape(&self) -> Shape {
+        match self {
+            Type::FiveOfKind => vec![5],
+            Type::FourOfKind => vec![4, 1],
+            Type::FullHouse => vec![3, 2],
+            Type::ThreeOfKind => vec![3, 1, 1],
+            Type::TwoPair => vec![2, 2, 1],
+            Type::OnePair => vec![2, 1, 1, 1],
+            Type::HighCard => vec![1; 5],
+        }
+    }
+    fn of(shape: &[usize]) -> Option<Type> {
+        TYPES.into_iter().find(|t| t.shape() == shape)
+    }
+}
+
 impl Solver for Day {
     fn pt1(&self, input: &str) -> String {
         format!(
             "{}",
-            Hands::from(input)
-                .ranked(false)
-                .iter()
-                .enumerate()
-                .map(|(i, hand)| (i as u64 + 1) * hand.1)
-                .sum::<u64>()
+            Hands::from(input).winnings(&Rules::standard()).unwrap()
         )
     }
     fn pt2(&self, input: &str) -> String {
-        format!(
-            "{}",
-            Hands::from(input)
-                .ranked(true)
-                .iter()
-                .enumerate()
-                .map(|(i, hand)| (i as u64 + 1) * hand.1)
-                .sum::<u64>()
-        )
+        format!("{}", Hands::from(input).winnings(&Rules::jokers()).unwrap())
+    }
+    fn debug(&self, input: &str) -> Option<String> {
+        let hands = Hands::from(input);
+        let mut out = String::new();
+        for (name, rules) in [("standard", Rules::standard()), ("jokers", Rules::jokers())] {
+            writeln!(out, "{name}:").unwrap();
+            let ranked = hands.ranked(&rules).unwrap();
+            for (rank, hand) in ranked.iter().enumerate() {
+                let shape = rules.hand_type(hand).unwrap();
+                let hand_type = Type::of(&shape).map_or(format!("{shape:?}"), |t| format!("{t:?}"));
+                let tied = rank > 0 && rules.compare(&ranked[rank - 1], hand) == Ordering::Equal;
+                writeln!(
+                    out,
+                    "{:>5} {} {hand_type:<11} bid {}{}",
+                    rank + 1,
+                    hand.0,
+                    hand.1,
+                    if tied {
+                        ", tied with the hand above"
+                    } else {
+                        ""
+                    }
+                )
+                .unwrap();
+            }
+        }
+        Some(out)
     }
 }
 
-const CARDS: [char; 13] = [
-    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
-];
-const CARDS_WITH_JOKERS: [char; 13] = [
-    'A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J',
-];
-const JOKER: char = 'J';
+#[derive(Debug)]
+struct Rules {
+    strengths: HashMap<char, usize>,
+    wildcards: Vec<char>,
+    hand_size: usize,
+    types: Vec<Shape>,
+}
 
-impl Hands<'_> {
-    fn ranked(&self, with_jokers: bool) -> Vec<Hand> {
-        let cards = if with_jokers {
-            CARDS_WITH_JOKERS
-        } else {
-            CARDS
-        };
-        let mut ranked = self.0.clone();
-        ranked.sort_by(|a_card, b_card| {
-            let by_hand_type =
-                (a_card.hand_type(with_jokers) as u64).cmp(&(b_card.hand_type(with_jokers) as u64));
-            if by_hand_type == Ordering::Equal {
-                for (a, b) in a_card.0.chars().zip(b_card.0.chars()) {
-                    let a_strength = cards
-                        .into_iter()
-                        .enumerate()
-                        .find(|(_, c)| *c == a)
-                        .unwrap()
-                        .0;
-                    let b_strength = cards
-                        .into_iter()
-                        .enumerate()
-                        .find(|(_, c)| *c == b)
-                        .unwrap()
-                        .0;
-                    match a_strength.cmp(&b_strength) {
-                        Ordering::Less => return Ordering::Greater,
-                        Ordering::Equal => {}
-                        Ordering::Greater => return Ordering::Less,
-                    }
-                }
-                panic!("Couldn't sort hands");
+impl Rules {
+    /// Rules with `cards` and `types` listed strongest first. Wildcards must
+    /// be among the cards, and take their place in the ordering when two
+    /// hands of the same type are compared.
+    fn new(
+        cards: &str,
+        wildcards: &str,
+        hand_size: usize,
+        types: Vec<Shape>,
+    ) -> Result<Self, String> {
+        let strengths: HashMap<char, usize> = cards
+            .chars()
+            .rev()
+            .enumerate()
+            .map(|(strength, card)| (card, strength))
+            .collect();
+        if strengths.len() != cards.chars().count() {
+            return Err(format!("duplicate card in '{cards}'"));
+        }
+        if let Some(card) = wildcards.chars().find(|c| !strengths.contains_key(c)) {
+            return Err(format!("wildcard '{card}' is not a card"));
+        }
+        if wildcards.chars().collect::<HashSet<_>>().len() != wildcards.chars().count() {
+            return Err(format!("duplicate wildcard in '{wildcards}'"));
+        }
+        if let Some(shape) = types
+            .iter()
+            .find(|shape| shape.iter().sum::<usize>() != hand_size)
+        {
+            return Err(format!(
+                "hand type {shape:?} does not have {hand_size} cards"
+            ));
+        }
+        Ok(Self {
+            strengths,
+            wildcards: wildcards.chars().collect(),
+            hand_size,
+            types,
+        })
+    }
+
+    fn standard() -> Self {
+        Self::new("AKQJT98765432", "", 5, TYPES.map(|t| t.shape()).to_vec()).unwrap()
+    }
+
+    fn jokers() -> Self {
+        Self::new("AKQT98765432J", "J", 5, TYPES.map(|t| t.shape()).to_vec()).unwrap()
+    }
+
+    fn strength(&self, card: char) -> Result<usize, String> {
+        self.strengths
+            .get(&card)
+            .copied()
+            .ok_or_else(|| format!("unknown card '{card}'"))
+    }
+
+    /// The strongest hand type the hand makes, with each wildcard joining
+    /// any group of cards or starting one of its own.
+    fn hand_type(&self, hand: &Hand) -> Result<Shape, String> {
+        if hand.0.chars().count() != self.hand_size {
+            return Err(format!(
+                "hand '{}' does not have {} cards",
+                hand.0, self.hand_size
+            ));
+        }
+        let mut groups: HashMap<char, usize> = HashMap::new();
+        let mut wildcards = 0;
+        for card in hand.0.chars() {
+            self.strength(card)?;
+            if self.wildcards.contains(&card) {
+                wildcards += 1;
             } else {
-                by_hand_type
+                *groups.entry(card).or_default() += 1;
             }
-        });
-        ranked
+        }
+        let mut shape: Shape = groups.into_values().collect();
+        shape.sort_unstable_by(|a, b| b.cmp(a));
+
+        // A wildcard can only start a group for a card not yet in the hand.
+        let naturals = self.strengths.len() - self.wildcards.len();
+        let mut shapes = BTreeSet::from([shape]);
+        for _ in 0..wildcards {
+            shapes = shapes
+                .iter()
+                .flat_map(|shape| {
+                    let grown = (0..shape.len()).map(move |i| {
+                        let mut grown = shape.clone();
+                        grown[i] += 1;
+                        grown
+                    });
+                    let started = (shape.len() < naturals).then(|| {
+                        let mut started = shape.clone();
+                        started.push(1);
+                        started
+                    });
+                    grown.chain(started)
+                })
+                .map(|mut shape| {
+                    shape.sort_unstable_by(|a, b| b.cmp(a));
+                    shape
+                })
+                .collect();
+        }
+        self.types
+            .iter()
+            .find(|t| shapes.contains(*t))
+            .cloned()
+            .ok_or_else(|| format!("hand '{}' has no hand type", hand.0))
+    }
+
+    fn key(&self, hand: &Hand) -> Result<(usize, Vec<usize>), String> {
+        let hand_type = self.hand_type(hand)?;
+        let rank = self.types.len() - self.types.iter().position(|t| *t == hand_type).unwrap();
+        let cards = hand
+            .0
+            .chars()
+            .map(|card| self.strength(card))
+            .collect::<Result<_, _>>()?;
+        Ok((rank, cards))
+    }
+
+    fn compare(&self, a: &Hand, b: &Hand) -> Ordering {
+        self.key(a).unwrap().cmp(&self.key(b).unwrap())
     }
 }
 
-impl<'a> Hand<'a> {
-    fn hand_type(&self, with_jokers: bool) -> Type {
-        let jokers = if with_jokers {
-            self.0.matches(JOKER).count()
-        } else {
-            0
-        };
-        let mut num_cards: Vec<usize> = CARDS
-            .into_iter()
-            .filter(|c| !with_jokers || *c != JOKER)
-            .map(|c| self.0.matches(c).count())
-            .filter(|&n| n > 0)
-            .collect();
-        num_cards.sort_unstable();
-        num_cards.reverse();
-        let first = num_cards.first().unwrap_or(&0);
-        match first + jokers {
-            5 => Type::FiveOfKind,
-            4 => Type::FourOfKind,
-            3 => match num_cards[1..].first().unwrap() {
-                2 => Type::FullHouse,
-                _ => Type::ThreeOfKind,
-            },
-            2 => match num_cards[1..].first().unwrap() {
-                2 => Type::TwoPair,
-                _ => Type::OnePair,
-            },
-            1 => Type::HighCard,
-            _ => panic!("Unhandled occurence: {first}"),
-        }
+impl Hands<'_> {
+    /// The hands from weakest to strongest. Hands with the same cards keep
+    /// their order from the input.
+    fn ranked(&self, rules: &Rules) -> Result<Vec<Hand<'_>>, String> {
+        let mut keyed = self
+            .0
+            .iter()
+            .map(|hand| rules.key(hand).map(|key| (key, hand.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(keyed.into_iter().map(|(_, hand)| hand).collect())
+    }
+    fn winnings(&self, rules: &Rules) -> Result<u64, String> {
+        Ok(self
+            .ranked(rules)?
+            .iter()
+            .zip(1..)
+            .map(|(hand, rank)| rank * hand.1)
+            .sum())
     }
 }
 
@@ -163,7 +285,7 @@ QQQJA 483"
         let actual: Vec<Type> = hands
             .0
             .into_iter()
-            .map(|hand| hand.hand_type(false))
+            .map(|hand| Type::of(&Rules::standard().hand_type(&hand).unwrap()).unwrap())
             .collect();
         let expected: Vec<Type> = vec![
             Type::OnePair,
@@ -181,7 +303,7 @@ QQQJA 483"
         let actual: Vec<Type> = hands
             .0
             .into_iter()
-            .map(|hand| hand.hand_type(true))
+            .map(|hand| Type::of(&Rules::jokers().hand_type(&hand).unwrap()).unwrap())
             .collect();
         let expected: Vec<Type> = vec![
             Type::OnePair,
@@ -197,9 +319,13 @@ QQQJA 483"
     fn test_hands_ranked_without_jokers() {
         let hands: Hands = Hands::from(example_input());
         let actual: Vec<(&str, Type, u64)> = hands
-            .ranked(false)
+            .ranked(&Rules::standard())
+            .unwrap()
             .iter()
-            .map(|hand| (hand.0, hand.hand_type(false), hand.1))
+            .map(|hand| {
+                let hand_type = Rules::standard().hand_type(hand).unwrap();
+                (hand.0, Type::of(&hand_type).unwrap(), hand.1)
+            })
             .collect();
         let expected: Vec<(&str, Type, u64)> = vec![
             ("32T3K", Type::OnePair, 765),
@@ -215,9 +341,13 @@ QQQJA 483"
     fn test_hands_ranked_with_jokers() {
         let hands: Hands = Hands::from(example_input());
         let actual: Vec<(&str, Type, u64)> = hands
-            .ranked(true)
+            .ranked(&Rules::jokers())
+            .unwrap()
             .iter()
-            .map(|hand| (hand.0, hand.hand_type(true), hand.1))
+            .map(|hand| {
+                let hand_type = Rules::jokers().hand_type(hand).unwrap();
+                (hand.0, Type::of(&hand_type).unwrap(), hand.1)
+            })
             .collect();
         let expected: Vec<(&str, Type, u64)> = vec![
             ("32T3K", Type::OnePair, 765),
@@ -229,6 +359,80 @@ QQQJA 483"
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_ties_keep_input_order() {
+        let hands = Hands::from("KK677 1\nQQQQQ 2\nKK677 3\nKK677 4");
+        let bids: Vec<u64> = hands
+            .ranked(&Rules::standard())
+            .unwrap()
+            .iter()
+            .map(|hand| hand.1)
+            .collect();
+        assert_eq!(vec![1, 3, 4, 2], bids);
+        assert_eq!(
+            Ordering::Equal,
+            Rules::standard().compare(&Hand("KK677", 1), &Hand("KK677", 3))
+        );
+        assert_eq!(
+            Ordering::Less,
+            Rules::jokers().compare(&Hand("JKKK2", 0), &Hand("QQQQ2", 0))
+        );
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        // Three card hands where deuces are wild as well as jokers, and a
+        // flush of sorts: three different cards beat a pair.
+        let rules = Rules::new("AKQJ2", "J2", 3, vec![vec![3], vec![1, 1, 1], vec![2, 1]]).unwrap();
+        assert_eq!(Ok(vec![3]), rules.hand_type(&Hand("A2J", 0)));
+        assert_eq!(Ok(vec![2, 1]), rules.hand_type(&Hand("AKK", 0)));
+        assert_eq!(Ok(vec![1, 1, 1]), rules.hand_type(&Hand("AKQ", 0)));
+        assert_eq!(Ok(vec![3]), rules.hand_type(&Hand("KKK", 0)));
+        // Only A, K and Q are natural, so three wildcards are still three of a kind.
+        assert_eq!(Ok(vec![3]), rules.hand_type(&Hand("2J2", 0)));
+        let hands = Hands::from("AKK 1\nAKQ 2\nQ2Q 3\nQQA 4");
+        assert_eq!(
+            Ok(vec![4, 1, 2, 3]),
+            hands
+                .ranked(&rules)
+                .map(|ranked| ranked.iter().map(|hand| hand.1).collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_and_hands() {
+        assert_eq!(
+            Err("wildcard '*' is not a card".to_string()),
+            Rules::new("AK", "*", 2, vec![]).map(|_| ())
+        );
+        assert_eq!(
+            Err("duplicate card in 'AKA'".to_string()),
+            Rules::new("AKA", "", 2, vec![]).map(|_| ())
+        );
+        assert_eq!(
+            Err("duplicate wildcard in 'JJJ'".to_string()),
+            Rules::new("AJ", "JJJ", 2, vec![vec![2]]).map(|_| ())
+        );
+        assert_eq!(
+            Err("hand type [2, 1] does not have 2 cards".to_string()),
+            Rules::new("AK", "", 2, vec![vec![2], vec![2, 1]]).map(|_| ())
+        );
+        let rules = Rules::standard();
+        assert_eq!(
+            Err("hand 'AKQ' does not have 5 cards".to_string()),
+            Hands::from("AKQ 1").ranked(&rules)
+        );
+        assert_eq!(
+            Err("unknown card '1'".to_string()),
+            Hands::from("AKQ1T 1").ranked(&rules)
+        );
+        let pairs_only = Rules::new("AK", "", 2, vec![vec![2]]).unwrap();
+        assert_eq!(
+            Err("hand 'AK' has no hand type".to_string()),
+            Hands::from("AK 1").ranked(&pairs_only)
+        );
+    }
+
     #[test]
     fn test_pt1_example() {
         assert_eq!("6440".to_string(), Day {}.pt1(example_input()))
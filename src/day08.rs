@@ -1,6 +1,7 @@
-use num::integer::lcm;
 use std::str::FromStr;
 
+use crate::libs::cycle;
+use crate::libs::parse::{self, Scanner};
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -42,10 +43,11 @@ impl Map {
             .filter(|(name, _, _)| start(name))
             .collect();
 
-        elements
+        let cycles: Vec<(usize, usize)> = elements
             .into_iter()
             .map(|e| {
                 let mut element = e;
+                let mut first = None;
                 for i in 0.. {
                     let instruction = instructions[i % instructions.len()];
                     element = &self.elements[match instruction {
@@ -54,13 +56,16 @@ impl Map {
                         _ => panic!("Unknown instruction: {instruction}"),
                     }];
                     if end(&element.0) {
-                        return i + 1;
+                        match first {
+                            None => first = Some(i + 1),
+                            Some(first) => return (first, i + 1 - first),
+                        }
                     }
                 }
                 panic!("foo");
             })
-            .reduce(lcm)
-            .unwrap()
+            .collect();
+        cycle::align(&cycles).unwrap()
     }
 }
 
@@ -73,17 +78,20 @@ impl FromStr for Map {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (instructions, elements) = s.split_once("\n\n").unwrap();
+        let [instructions, elements] = parse::sections(s)[..] else {
+            return Err(String::from("expected instructions and elements"));
+        };
         let instructions = String::from(instructions);
-        let elements: Vec<(String, String, String)> = elements
-            .lines()
-            .map(|line| {
-                let (name, left_right) = line.split_once(" = ").unwrap();
-                let left_right = left_right.replace(['(', ')'], "");
-                let (left, right) = left_right.split_once(", ").unwrap();
-                (String::from(name), String::from(left), String::from(right))
-            })
-            .collect();
+        let elements: Vec<(String, String, String)> = parse::lines(elements, |line| {
+            let mut scanner = Scanner::new(line);
+            let name = scanner.word()?;
+            scanner.tag(" = (")?;
+            let left = scanner.word()?;
+            scanner.tag(", ")?;
+            let right = scanner.word()?;
+            scanner.tag(")")?.end()?;
+            Ok((String::from(name), String::from(left), String::from(right)))
+        })?;
         let elements: Vec<(String, usize, usize)> = elements
             .iter()
             .map(|(name, left, right)| {
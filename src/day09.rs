@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use crate::libs::{parse, polynomial};
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -23,31 +24,21 @@ impl FromStr for Measurements {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Measurements(
-            s.split_ascii_whitespace()
-                .map(|n| n.parse::<i64>().unwrap())
-                .collect(),
-        ))
+        Ok(Measurements(parse::ints(s)?))
     }
 }
 impl Measurements {
     fn next_history(&self) -> i64 {
-        let next: Vec<i64> = self
-            .0
-            .windows(2)
-            .map(|a| a.last().unwrap() - a.first().unwrap())
-            .collect();
-
-        return if next.iter().all(|&n| n == 0) {
-            self.0.last().unwrap() + next.last().unwrap()
-        } else {
-            self.0.last().unwrap() + Measurements(next).next_history()
-        };
+        polynomial::difference_table(&self.0)
+            .iter()
+            .map(|row| row.last().unwrap())
+            .sum()
     }
     fn prev_history(&self) -> i64 {
-        let mut reversed = self.0.clone();
-        reversed.reverse();
-        Measurements(reversed).next_history()
+        polynomial::difference_table(&self.0)
+            .iter()
+            .rev()
+            .fold(0, |below, row| row[0] - below)
     }
 }
 
@@ -56,7 +47,9 @@ impl FromStr for Oasis {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Oasis(s.lines().map(|line| line.parse().unwrap()).collect()))
+        Ok(Oasis(parse::lines(s, |line| {
+            Ok(Measurements(parse::ints(line)?))
+        })?))
     }
 }
 
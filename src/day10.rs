@@ -1,7 +1,12 @@
+use std::collections::HashSet;
+use std::fmt::Display;
 use std::str::FromStr;
 
-use crate::libs::{shoelace, Coordinate};
-use crate::problem::Solver;
+use crate::libs::polygon::{Location, Polygon};
+use crate::libs::region;
+use crate::libs::render::{Color, Renderer, Style};
+use crate::libs::{Coordinate, Grid};
+use crate::problem::{Image, Solver};
 
 pub struct Day {}
 
@@ -17,6 +22,21 @@ impl Solver for Day {
         let enclosed = pipe_maze.enclosed();
         format!("{enclosed}")
     }
+    fn debug(&self, input: &str) -> Option<String> {
+        Some(
+            input
+                .parse::<PipeMaze>()
+                .unwrap()
+                .render(|renderer| renderer.ansi()),
+        )
+    }
+    fn image(&self, input: &str, format: Image) -> Option<String> {
+        let maze = input.parse::<PipeMaze>().unwrap();
+        Some(match format {
+            Image::Ppm => maze.render(|renderer| renderer.ppm(4)),
+            Image::Svg => maze.render(|renderer| renderer.svg(8)),
+        })
+    }
 }
 
 pub(crate) fn input() -> &'static str {
@@ -28,9 +48,9 @@ impl PipeMaze {
         let start = self.start();
         let mut connections = self.connections(&start);
 
-        let mut cycle: Vec<Coordinate<isize>> = vec![start.clone()];
-        let mut from = start.clone();
-        let mut to = connections.next().unwrap().clone();
+        let mut from = start;
+        let mut to = connections.next().unwrap();
+        let mut cycle: Vec<Coordinate<isize>> = vec![start, to];
         while to != start {
             let new_tos: Vec<Coordinate<isize>> =
                 self.connections(&to).filter(|c| c != &from).collect();
@@ -39,16 +59,78 @@ impl PipeMaze {
                 "expected single connection, got: {new_tos:?} (from: {from}, to: {to})"
             );
 
-            let new_to = new_tos.first().unwrap().clone();
+            let new_to = *new_tos.first().unwrap();
             from = to;
             to = new_to;
-            cycle.push(to.clone());
+            cycle.push(to);
         }
         cycle
     }
 
     fn enclosed(&self) -> isize {
-        shoelace(&self.cycle()) - isize::try_from(self.cycle().len()).unwrap()
+        Polygon::from(&self.cycle()).interior_points()
+    }
+
+    /// The maze with the loop drawn in yellow, the start in red, and the tiles
+    /// inside and outside the loop in green and blue. Inside is decided by the
+    /// polygon and outside by flooding the map, so a tile left uncoloured is
+    /// one the two disagree on.
+    fn render(&self, output: impl FnOnce(&Renderer<PipeType>) -> String) -> String {
+        let grid = Grid {
+            points: self.pipes.clone(),
+            width: self.width,
+            height: self.pipes.len() / self.width,
+        };
+        let cycle = self.cycle();
+        let polygon = Polygon::from(&cycle);
+        let unsigned = |c: &Coordinate<isize>| {
+            Coordinate(usize::try_from(c.0).unwrap(), usize::try_from(c.1).unwrap())
+        };
+        let path: Vec<Coordinate<usize>> = cycle.iter().map(unsigned).collect();
+
+        // Flood at double resolution, so that water can squeeze between pipes
+        // that touch without being connected.
+        let mut on_loop = Grid::new(grid.width, grid.height, false);
+        path.iter().for_each(|c| on_loop[c] = true);
+        let joined: HashSet<(Coordinate<usize>, Coordinate<usize>)> = path
+            .windows(2)
+            .flat_map(|w| [(w[0], w[1]), (w[1], w[0])])
+            .collect();
+        let upscaled = region::upscale(
+            &on_loop,
+            |&wall| wall,
+            |c, direction| joined.contains(&(c, c + direction)),
+        );
+        let regions = region::label(&upscaled, |&wall| !wall);
+        let open: Vec<bool> = (0..regions.count())
+            .map(|r| regions.touches_edge(r))
+            .collect();
+        let outside = |c: &Coordinate<usize>| {
+            regions
+                .region(&Coordinate(2 * c.0, 2 * c.1))
+                .is_some_and(|r| open[r])
+        };
+
+        let renderer = Renderer::new(&grid, |position, pipe: &PipeType| {
+            let signed = Coordinate(
+                isize::try_from(position.0).unwrap(),
+                isize::try_from(position.1).unwrap(),
+            );
+            let inside = polygon.locate(&signed) == Location::Inside;
+            let background = match (inside, outside(&position)) {
+                (true, false) => Some(Color::GREEN),
+                (false, true) => Some(Color::BLUE),
+                _ => None,
+            };
+            Style {
+                foreground: background.map(|_| Color::BLACK),
+                background,
+                ..Style::plain(pipe.glyph())
+            }
+        })
+        .path(&path, Color::YELLOW)
+        .highlight([unsigned(&self.start())], Color::RED);
+        output(&renderer)
     }
 
     fn start(&self) -> Coordinate<isize> {
@@ -226,18 +308,24 @@ impl PipeType {
     }
 }
 
-impl ToString for PipeType {
-    fn to_string(&self) -> String {
-        String::from(match self {
-            PipeType::NorthSouth => "|",
-            PipeType::EastWest => "-",
-            PipeType::NorthEast => "L",
-            PipeType::NorthWest => "J",
-            PipeType::SouthWest => "7",
-            PipeType::SouthEast => "F",
-            PipeType::Ground => ".",
-            PipeType::Start => "S",
-        })
+impl PipeType {
+    fn glyph(&self) -> char {
+        match self {
+            PipeType::NorthSouth => '|',
+            PipeType::EastWest => '-',
+            PipeType::NorthEast => 'L',
+            PipeType::NorthWest => 'J',
+            PipeType::SouthWest => '7',
+            PipeType::SouthEast => 'F',
+            PipeType::Ground => '.',
+            PipeType::Start => 'S',
+        }
+    }
+}
+
+impl Display for PipeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.glyph())
     }
 }
 impl FromStr for PipeType {
@@ -374,6 +462,30 @@ L7JLJL-JLJLJL--JLJ.L"
         )
     }
 
+    #[test]
+    fn test_render() {
+        let maze = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."
+            .parse::<PipeMaze>()
+            .unwrap();
+        let rendered = maze.render(|renderer| renderer.ansi());
+        let background = |Color(r, g, b): Color| format!("\x1b[48;2;{r};{g};{b}m");
+        assert_eq!(4, rendered.matches(&background(Color::GREEN)).count());
+        assert_eq!(
+            99 - 46 - 4,
+            rendered.matches(&background(Color::BLUE)).count()
+        );
+        assert_eq!(45, rendered.matches(&background(Color::YELLOW)).count());
+        assert_eq!(1, rendered.matches(&background(Color::RED)).count());
+    }
+
     #[test]
     fn test_pt2() {
         assert_eq!("303".to_string(), Day {}.pt2(input()))
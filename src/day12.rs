@@ -1,5 +1,6 @@
 use std::fmt::Write;
 
+use crate::libs::memo::Memoized;
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -44,39 +45,41 @@ impl<'a> From<&'a str> for ConditionRecords<'a> {
 }
 
 fn permutations(springs: &str, groups: &[usize]) -> usize {
-    let springs = format!(".{}", springs.trim_end_matches('.'));
-    let springs: Vec<char> = springs.chars().collect();
-    let mut count = vec![0_usize; springs.len() + 1];
-    count[0] = 1;
-
-    for (i, _) in springs.iter().take_while(|&&c| c != '#').enumerate() {
-        count[i + 1] = 1;
-    }
-
-    for group in groups {
-        let mut n_c = vec![0; springs.len() + 1];
-        let mut chunk = 0;
-
-        for (i, &c) in springs.iter().enumerate() {
-            if c == '.' {
-                chunk = 0;
-            } else {
-                chunk += 1;
-            }
-
-            if c != '#' {
-                n_c[i + 1] += n_c[i];
+    let springs = springs.as_bytes();
+    // The fewest springs that can hold `groups[g..]`, with a gap between each.
+    let needed: Vec<usize> = (0..=groups.len())
+        .map(|g| groups[g..].iter().sum::<usize>() + groups.len().saturating_sub(g + 1))
+        .collect();
+    // Arrangements of `groups[g..]` within `springs[i..]`.
+    let mut arrangements = Memoized::new(
+        |arrangements: &mut dyn FnMut((usize, usize)) -> usize, (i, g): (usize, usize)| {
+            let Some(&group) = groups.get(g) else {
+                return usize::from(springs.iter().skip(i).all(|&s| s != b'#'));
+            };
+            let Some(&spring) = springs.get(i) else {
+                return 0;
+            };
+
+            let mut count = 0;
+            // Skipping this spring only helps when the rest can still hold
+            // every remaining group. Placing a group here never needs the
+            // check, as the groups after it need that much less room.
+            if spring != b'#' && springs.len() - (i + 1) >= needed[g] {
+                count += arrangements((i + 1, g));
             }
-
-            if chunk >= *group && springs[i - group] != '#' {
-                n_c[i + 1] += count[i - group];
+            let end = i + group;
+            if end <= springs.len()
+                && !springs[i..end].contains(&b'.')
+                && springs.get(end) != Some(&b'#')
+            {
+                count += arrangements((end + 1, g + 1));
             }
-        }
-
-        count = n_c;
-    }
-
-    *count.last().unwrap()
+            count
+        },
+    );
+    // At most one result per spring and group.
+    arrangements.reserve(springs.len() * (groups.len() + 1));
+    arrangements.call((0, 0))
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -1,3 +1,4 @@
+use crate::libs::parse;
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -105,7 +106,7 @@ impl<'a> Map<'a> {
                     .len()
             })
             .sum();
-        let is_reflection = lines.len() % 2 == 0 && diffs == required_smudges;
+        let is_reflection = lines.len().is_multiple_of(2) && diffs == required_smudges;
 
         (is_reflection, diffs)
     }
@@ -136,7 +137,7 @@ impl<'a> Map<'a> {
 struct Maps<'a>(Vec<Map<'a>>);
 impl<'a> From<&'a str> for Maps<'a> {
     fn from(value: &'a str) -> Self {
-        Maps(value.split("\n\n").map(Map::from).collect())
+        Maps(parse::sections(value).into_iter().map(Map::from).collect())
     }
 }
 
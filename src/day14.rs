@@ -1,5 +1,6 @@
 use std::{cmp::Ordering, fmt::Debug};
 
+use crate::libs::cycle;
 use crate::problem::Solver;
 
 pub struct Day {}
@@ -12,36 +13,23 @@ impl Solver for Day {
         format!("{load}")
     }
     fn pt2(&self, input: &str) -> String {
-        let mut dish = ParabolicReflectorDish::from(input.to_owned());
-        let mut patterns: Vec<Vec<char>> = vec![];
-
-        let cycles = 1_000_000_000;
-        for i in 0..cycles {
-            dish.rotate_360();
-            patterns.push(dish.0.clone());
-            if let Some((index, _)) = patterns
-                .iter()
-                .take(patterns.len() - 1)
-                .enumerate()
-                .find(|(_, p)| **p == dish.0)
-            {
-                let cycle_len = i - index;
-                let cycles_to_go = (cycles - i - 1) % cycle_len;
-
-                for _ in 0..cycles_to_go {
-                    dish.rotate_360();
-                }
-
-                break;
-            }
-        }
+        let dish = ParabolicReflectorDish::from(input.to_owned());
+        let dish = cycle::state_at(
+            dish,
+            |dish| {
+                let mut dish = dish.clone();
+                dish.rotate_360();
+                dish
+            },
+            1_000_000_000,
+        );
 
         let load = dish.load();
         format!("{load}")
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 struct ParabolicReflectorDish(Vec<char>, usize);
 impl From<String> for ParabolicReflectorDish {
     fn from(value: String) -> Self {
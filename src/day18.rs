@@ -1,5 +1,7 @@
+use std::fmt::Write;
+
 use crate::{
-    libs::{shoelace, Coordinate},
+    libs::{polygon::Polygon, Coordinate},
     problem::Solver,
 };
 
@@ -16,6 +18,25 @@ impl Solver for Day {
         let dug = lavaduct_lagoon.dig(&InstructionType::Color);
         format!("{dug}")
     }
+    fn debug(&self, input: &str) -> Option<String> {
+        let lavaduct_lagoon = LavaductLagoon::from(input);
+        let mut out = String::new();
+        for (part, instruction_type) in [
+            ("pt1", InstructionType::Literal),
+            ("pt2", InstructionType::Color),
+        ] {
+            let lagoon = lavaduct_lagoon.outline(&instruction_type);
+            writeln!(
+                out,
+                "{part}: dug {:?}, area {}, trench {} long",
+                lagoon.orientation(),
+                lagoon.area(),
+                lagoon.perimeter()
+            )
+            .unwrap();
+        }
+        Some(out)
+    }
 }
 
 enum InstructionType {
@@ -27,6 +48,9 @@ struct LavaductLagoon<'a>(Vec<Instruction<'a>>);
 
 impl<'a> LavaductLagoon<'a> {
     fn dig(&self, instruction_type: &InstructionType) -> isize {
+        self.outline(instruction_type).covered_cells()
+    }
+    fn outline(&self, instruction_type: &InstructionType) -> Polygon {
         let start: Coordinate<isize> = Coordinate(0, 0);
         let dig_points: Vec<Coordinate<isize>> = self.0.iter().fold(vec![start], |mut acc, dig| {
             let Coordinate(x, y) = *acc.last().unwrap();
@@ -44,7 +68,7 @@ impl<'a> LavaductLagoon<'a> {
             acc
         });
 
-        shoelace(&dig_points)
+        Polygon::from(&dig_points)
     }
 }
 
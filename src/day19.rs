@@ -1,5 +1,6 @@
-use std::{collections::HashMap, ops::RangeInclusive};
+use std::collections::HashMap;
 
+use crate::libs::interval::IntervalSet;
 use crate::problem::Solver;
 pub struct Day {}
 
@@ -13,13 +14,18 @@ impl Solver for Day {
         let workflows = Workflows::from(input);
         let processed = workflows.process_range(
             Part {
-                x: 1..=4000,
-                m: 1..=4000,
-                a: 1..=4000,
-                s: 1..=4000,
+                x: IntervalSet::from(1..4001),
+                m: IntervalSet::from(1..4001),
+                a: IntervalSet::from(1..4001),
+                s: IntervalSet::from(1..4001),
             },
             &ProcessResult::Next("in"),
-            |part| part.a.len() * part.m.len() * part.s.len() * part.x.len(),
+            |part| {
+                [&part.a, &part.m, &part.s, &part.x]
+                    .iter()
+                    .map(|attr| usize::from(attr.len()))
+                    .product()
+            },
         );
         format!("{processed}")
     }
@@ -29,7 +35,15 @@ impl Part {
     fn is_empty(&self) -> bool {
         self.a.is_empty() || self.m.is_empty() || self.s.is_empty() || self.x.is_empty()
     }
-    fn with_attr(&self, attr: &Attr, value: RangeInclusive<u16>) -> Part {
+    fn attr(&self, attr: &Attr) -> &IntervalSet<u16> {
+        match attr {
+            Attr::A => &self.a,
+            Attr::M => &self.m,
+            Attr::S => &self.s,
+            Attr::X => &self.x,
+        }
+    }
+    fn with_attr(&self, attr: &Attr, value: IntervalSet<u16>) -> Part {
         match attr {
             Attr::A => Part {
                 a: value,
@@ -66,13 +80,14 @@ impl<'a> Workflows<'a> {
             .into_iter()
             .map(|part| {
                 self.process_range(part, &ProcessResult::Next("in"), |p| {
-                    let score =
-                        if p.a.is_empty() || p.m.is_empty() || p.s.is_empty() || p.x.is_empty() {
-                            0
-                        } else {
-                            p.a.start() + p.m.start() + p.s.start() + p.x.start()
-                        };
-                    usize::try_from(score).unwrap()
+                    if p.is_empty() {
+                        0
+                    } else {
+                        [&p.a, &p.m, &p.s, &p.x]
+                            .iter()
+                            .map(|attr| usize::from(attr.min().unwrap()))
+                            .sum()
+                    }
                 })
             })
             .sum::<usize>()
@@ -93,52 +108,21 @@ impl<'a> Workflows<'a> {
                             break;
                         }
                         Rule::Conditional(attr, operator, threshold, to) => {
-                            let value = match attr {
-                                Attr::A => &part.a,
-                                Attr::M => &part.m,
-                                Attr::S => &part.s,
-                                Attr::X => &part.x,
-                            };
-                            let adjusted_threshold: u16 = match operator {
-                                Operator::LessThan => *threshold - 1,
-                                Operator::GreaterThan => *threshold + 1,
-                            };
-                            if value.contains(&adjusted_threshold) {
-                                let matching = match operator {
-                                    Operator::LessThan => *value.start()..=adjusted_threshold,
-                                    Operator::GreaterThan => adjusted_threshold..=*value.end(),
-                                };
-                                if !matching.is_empty() {
-                                    let matching = part.with_attr(attr, matching);
-                                    sum += self.process_range(matching, to, score);
-
-                                    let non_matching = match operator {
-                                        Operator::LessThan => *threshold..=*value.end(),
-                                        Operator::GreaterThan => *value.start()..=*threshold,
-                                    };
-                                    let non_matching = part.with_attr(attr, non_matching);
-                                    if non_matching.is_empty() {
-                                        break;
-                                    }
-
-                                    part = non_matching;
+                            let (matching, non_matching) = match operator {
+                                Operator::LessThan => part.attr(attr).split_at(*threshold),
+                                Operator::GreaterThan => {
+                                    let (below, above) = part.attr(attr).split_at(*threshold + 1);
+                                    (above, below)
                                 }
-                            } else {
-                                match operator {
-                                    Operator::LessThan => {
-                                        if value.end() < threshold {
-                                            sum += self.process_range(part.clone(), to, score);
-                                            break;
-                                        }
-                                    }
-                                    Operator::GreaterThan => {
-                                        if value.start() > threshold {
-                                            sum += self.process_range(part.clone(), to, score);
-                                            break;
-                                        }
-                                    }
-                                };
+                            };
+                            if !matching.is_empty() {
+                                sum +=
+                                    self.process_range(part.with_attr(attr, matching), to, score);
+                            }
+                            if non_matching.is_empty() {
+                                break;
                             }
+                            part = part.with_attr(attr, non_matching);
                         }
                     }
                 }
@@ -251,10 +235,10 @@ impl<'a> From<&'a str> for Rule<'a> {
 
 #[derive(Clone, Debug)]
 struct Part {
-    x: RangeInclusive<u16>,
-    m: RangeInclusive<u16>,
-    a: RangeInclusive<u16>,
-    s: RangeInclusive<u16>,
+    x: IntervalSet<u16>,
+    m: IntervalSet<u16>,
+    a: IntervalSet<u16>,
+    s: IntervalSet<u16>,
 }
 impl From<&str> for Part {
     fn from(value: &str) -> Self {
@@ -263,11 +247,15 @@ impl From<&str> for Part {
             .map(|a| a.split_once('=').unwrap())
             .map(|(attr, value)| (Attr::from(attr), value.parse::<u16>().unwrap()))
             .collect();
+        let single = |attr: Attr| {
+            let value = *attrs.get(&attr).unwrap();
+            IntervalSet::from(value..value + 1)
+        };
         Self {
-            x: *attrs.get(&Attr::X).unwrap()..=*attrs.get(&Attr::X).unwrap(),
-            m: *attrs.get(&Attr::M).unwrap()..=*attrs.get(&Attr::M).unwrap(),
-            a: *attrs.get(&Attr::A).unwrap()..=*attrs.get(&Attr::A).unwrap(),
-            s: *attrs.get(&Attr::S).unwrap()..=*attrs.get(&Attr::S).unwrap(),
+            x: single(Attr::X),
+            m: single(Attr::M),
+            a: single(Attr::A),
+            s: single(Attr::S),
         }
     }
 }
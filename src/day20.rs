@@ -1,6 +1,6 @@
-use num::integer::lcm;
 use std::{collections::HashMap, fmt::Debug};
 
+use crate::libs::cycle;
 use crate::problem::Solver;
 pub struct Day {}
 
@@ -25,20 +25,26 @@ impl Solver for Day {
         let vr_inputs = ["pq", "fg", "dk", "fm"];
         let vr_inputs: [usize; 4] =
             vr_inputs.map(|i| machines.0.iter().position(|m| m.label() == i).unwrap());
-        let mut cycles: Vec<usize> = vec![];
+        let mut presses: [Vec<usize>; 4] = Default::default();
 
         for i in 1.. {
             let signals = machines.broadcast(PulseType::Low, "broadcaster");
 
             for (from, pulse_type) in signals {
-                if vr_inputs.contains(&from) && pulse_type == PulseType::High {
-                    cycles.push(i);
+                if pulse_type != PulseType::High {
+                    continue;
+                }
+                if let Some(input) = vr_inputs.iter().position(|&vr_input| vr_input == from) {
+                    if presses[input].last() != Some(&i) {
+                        presses[input].push(i);
+                    }
                 }
             }
 
-            if cycles.len() == vr_inputs.len() {
-                let lcm = cycles.into_iter().reduce(lcm).unwrap();
-                let cycles = lcm;
+            if presses.iter().all(|p| p.len() >= 2) {
+                let cycles: Vec<(usize, usize)> =
+                    presses.iter().map(|p| (p[0], p[1] - p[0])).collect();
+                let cycles = cycle::align(&cycles).unwrap();
                 return format!("{cycles}");
             }
 
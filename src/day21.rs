@@ -1,6 +1,11 @@
-use std::{collections::HashSet, iter};
+use std::collections::VecDeque;
 
-use crate::{libs::Coordinate, problem::Solver};
+use num::ToPrimitive;
+
+use crate::{
+    libs::{bits::BitGrid, polynomial, tiled::Tiled, Coordinate, Grid, OFFSETS},
+    problem::Solver,
+};
 pub struct Day {}
 
 impl Solver for Day {
@@ -10,114 +15,114 @@ impl Solver for Day {
         format!("{plots}")
     }
     fn pt2(&self, input: &str) -> String {
-        let plots = Map::pt2(&input.bytes().collect::<Vec<_>>(), 26_501_365);
+        let plots = Map::pt2(&Grid::from(input), 26_501_365);
         format!("{plots}")
     }
 }
 
 impl Map {
-    fn pt2(input: &[u8], steps: u64) -> u64 {
-        let targets = [0, 65, 65 + 131, 65 + 2 * 131];
-        let start = (65i16, 65i16);
-        let a_b_c = targets
-            .windows(2)
-            .scan(HashSet::from_iter(iter::once(start)), |queue, w| {
-                let (start, end) = (w[0], w[1]);
-                for _ in start..end {
-                    *queue = queue
-                        .iter()
-                        .flat_map(|&(y, x)| {
-                            [(0, 1), (0, -1), (1, 0), (-1, 0)]
-                                .into_iter()
-                                .map(move |(dy, dx)| (y + dy, x + dx))
-                                .filter(|&(y, x)| {
-                                    input[(y.rem_euclid(131) as usize * 132)
-                                        + x.rem_euclid(131) as usize]
-                                        != b'#'
-                                })
-                        })
-                        .collect::<HashSet<_>>();
-                }
-                Some(queue.len() as u64)
-            })
-            .collect::<Vec<_>>();
-        let (a, b, c) = (a_b_c[0], a_b_c[1], a_b_c[2]);
+    fn pt2(grid: &Grid<u8>, steps: u64) -> u64 {
+        let tiled = Tiled::new(grid);
+        let size = tiled.width();
+        let start = Self::tiled_start(&tiled, grid);
+        // The reached plots only grow quadratically per whole copy of the
+        // grid walked when the walk crosses copies at the same step in every
+        // direction, which needs the start at the centre of a square grid.
+        assert_eq!(size, tiled.height(), "the grid must be square");
+        assert_eq!(
+            start,
+            Coordinate(size / 2, size / 2),
+            "the start must be at the centre of the grid"
+        );
+        let targets = [start.0, start.0 + size, start.0 + 2 * size];
+        let distances = Self::distances(&tiled, start, targets[2]);
+        // A plot can be stood on after any number of steps at least its
+        // distance with the same parity, as the walk can step back and forth.
+        let a_b_c = targets.map(|steps| {
+            distances
+                .points
+                .iter()
+                .flatten()
+                .filter(|&&distance| distance <= steps && distance % 2 == steps % 2)
+                .count() as i64
+        });
+
+        let x = i64::try_from(steps / u64::try_from(size).unwrap()).unwrap();
+        polynomial::extrapolate(&a_b_c, x)
+            .to_integer()
+            .to_u64()
+            .unwrap()
+    }
 
-        let x = steps / 131;
-        a + x * (b - a) + x * (x - 1) / 2 * ((c - b) - (b - a))
+    fn tiled_start(tiled: &Tiled<u8>, grid: &Grid<u8>) -> Coordinate<isize> {
+        let start = grid.coord(grid.points.iter().position(|&c| c == b'S').unwrap());
+        tiled.in_tile(&Coordinate(0, 0), &start)
+    }
+
+    /// The fewest steps to every plot within `reach` steps of `start` on the
+    /// infinite map, in a window centred on `start`.
+    fn distances(tiled: &Tiled<u8>, start: Coordinate<isize>, reach: isize) -> Grid<Option<isize>> {
+        let side = usize::try_from(2 * reach + 1).unwrap();
+        let window = |Coordinate(x, y): Coordinate<isize>| {
+            Coordinate(
+                usize::try_from(x - start.0 + reach).unwrap(),
+                usize::try_from(y - start.1 + reach).unwrap(),
+            )
+        };
+        let mut distances = Grid::new(side, side, None);
+        distances[&window(start)] = Some(0);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((Coordinate(x, y), distance)) = queue.pop_front() {
+            if distance == reach {
+                continue;
+            }
+            for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
+                let next = Coordinate(x + dx, y + dy);
+                if tiled[&next] != b'#' && distances[&window(next)].is_none() {
+                    distances[&window(next)] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+        distances
     }
 
-    fn plots(&self, steps: u64, visited: Option<HashSet<Coordinate<isize>>>) -> Option<u64> {
+    fn plots(&self, steps: u64, visited: Option<BitGrid>) -> Option<u64> {
         if steps == 0 {
-            visited.map(|v| u64::try_from(v.len()).unwrap())
+            visited.map(|v| u64::try_from(v.count_ones()).unwrap())
         } else {
-            let reachable: HashSet<Coordinate<isize>> = visited
-                .unwrap_or_else(|| HashSet::from_iter([self.start()]))
+            let visited = visited.unwrap_or_else(|| {
+                let mut start = BitGrid::new(self.width(), self.height());
+                start.insert(&self.start());
+                start
+            });
+            let reachable = OFFSETS
                 .iter()
-                .flat_map(|c| self.reachable(c))
-                .collect();
+                .map(|&direction| visited.shift(direction))
+                .reduce(|acc, shifted| &acc | &shifted)
+                .unwrap();
 
-            self.plots(steps - 1, Some(reachable))
+            self.plots(steps - 1, Some(&reachable & &self.garden_plots()))
         }
     }
-    fn start(&self) -> Coordinate<isize> {
-        let s = isize::try_from(
-            self.terrain
-                .iter()
-                .enumerate()
-                .find(|(_, t)| t == &&Terrain::Start)
-                .unwrap()
-                .0,
-        )
-        .unwrap();
-        Coordinate(s % self.width, s / self.width)
-    }
-    fn reachable(&self, from: &Coordinate<isize>) -> HashSet<Coordinate<isize>> {
-        [
-            self.up(from),
-            self.right(from),
-            self.down(from),
-            self.left(from),
-        ]
-        .into_iter()
-        .flatten()
-        .collect()
-    }
-    fn terrain_type(&self, coordinate: &Coordinate<isize>) -> Terrain {
-        self.terrain
-            [usize::try_from(coordinate.0 % self.width + coordinate.1 * self.width).unwrap()]
-    }
-    fn up(&self, from: &Coordinate<isize>) -> Option<Coordinate<isize>> {
-        if from.1 > 0 {
-            Some(Coordinate(from.0, from.1 - 1))
-        } else {
-            None
-        }
-        .filter(|c| self.terrain_type(c) != Terrain::Rock)
+    fn width(&self) -> usize {
+        usize::try_from(self.width).unwrap()
     }
-    fn right(&self, from: &Coordinate<isize>) -> Option<Coordinate<isize>> {
-        if from.0 < self.width - 1 {
-            Some(Coordinate(from.0 + 1, from.1))
-        } else {
-            None
-        }
-        .filter(|c| self.terrain_type(c) != Terrain::Rock)
+    fn height(&self) -> usize {
+        self.terrain.len() / self.width()
     }
-    fn down(&self, from: &Coordinate<isize>) -> Option<Coordinate<isize>> {
-        if from.1 < self.width - 1 {
-            Some(Coordinate(from.0, from.1 + 1))
-        } else {
-            None
-        }
-        .filter(|c| self.terrain_type(c) != Terrain::Rock)
+    fn start(&self) -> Coordinate<usize> {
+        let s = self
+            .terrain
+            .iter()
+            .position(|t| t == &Terrain::Start)
+            .unwrap();
+        Coordinate(s % self.width(), s / self.width())
     }
-    fn left(&self, from: &Coordinate<isize>) -> Option<Coordinate<isize>> {
-        if from.0 > 0 {
-            Some(Coordinate(from.0 - 1, from.1))
-        } else {
-            None
-        }
-        .filter(|c| self.terrain_type(c) != Terrain::Rock)
+    fn garden_plots(&self) -> BitGrid {
+        BitGrid::from_fn(self.width(), self.height(), |c| {
+            self.terrain[c.0 + c.1 * self.width()] != Terrain::Rock
+        })
     }
 }
 
@@ -212,7 +217,7 @@ mod tests {
     #[test]
     #[ignore]
     fn test_pt2_example() {
-        let plots = Map::pt2(&example_input().bytes().collect::<Vec<_>>(), 5000);
+        let plots = Map::pt2(&Grid::from(example_input()), 5000);
         assert_eq!(16733044, plots);
     }
 
@@ -4,6 +4,7 @@ use std::{
     ops::RangeInclusive,
 };
 
+use crate::libs::parse;
 use crate::problem::Solver;
 pub struct Day {}
 
@@ -121,9 +122,9 @@ impl From<&str> for Bricks {
 
 impl From<&str> for Brick {
     fn from(value: &str) -> Self {
-        let (a, b) = value.split_once('~').unwrap();
-        let start: Vec<usize> = a.split(',').map(|n| n.parse::<usize>().unwrap()).collect();
-        let end: Vec<usize> = b.split(',').map(|n| n.parse::<usize>().unwrap()).collect();
+        let (a, b) = parse::key_value(value, "~").unwrap();
+        let start: Vec<usize> = parse::list(a, ',').unwrap();
+        let end: Vec<usize> = parse::list(b, ',').unwrap();
 
         Self {
             x: start[0].min(end[0])..=start[0].max(end[0]),
@@ -145,7 +146,7 @@ impl Display for Bricks {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.bricks
             .iter()
-            .fold(Ok(()), |_, brick| f.write_fmt(format_args!("{}\n", &brick)))
+            .try_for_each(|brick| f.write_fmt(format_args!("{}\n", &brick)))
     }
 }
 impl Debug for Brick {
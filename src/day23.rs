@@ -1,11 +1,11 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::VecDeque,
     sync::atomic::{AtomicU32, Ordering},
     thread,
 };
 
 use crate::{
-    libs::{Coordinate, Grid, DOWN, LEFT, OFFSETS, RIGHT, UP},
+    libs::{bits::BitSet, graph::Graph, Coordinate, Direction, Grid, DOWN, LEFT, RIGHT, UP},
     problem::Solver,
 };
 pub struct Day {}
@@ -13,90 +13,87 @@ pub struct Day {}
 impl Solver for Day {
     fn pt1(&self, input: &str) -> String {
         let input = Input::from(input);
-        let mut cost = [0; 36];
-
-        let mut todo = VecDeque::new();
-        todo.push_back(input.start);
-
-        while let Some(from) = todo.pop_front() {
-            let mut nodes = input.directed[from];
-
-            while nodes > 0 {
-                let to = nodes.trailing_zeros() as usize;
-                let mask = 1 << to;
-                nodes ^= mask;
-
-                cost[to] = cost[to].max(cost[from] + input.weight[from][to]);
-                todo.push_back(to);
+        let trails = &input.directed();
+        let mut cost = vec![None; trails.node_count()];
+        cost[trails.index(&input.start).unwrap()] = Some(0);
+
+        // The slopes make the trails acyclic, so the longest path can be
+        // found in a single pass in topological order.
+        for from in trails.topological_sort().unwrap() {
+            let Some(so_far) = cost[from] else {
+                continue;
+            };
+            for &(to, weight) in trails.neighbours(from) {
+                cost[to] = cost[to].max(Some(so_far + weight));
             }
         }
 
-        let distance = cost[input.end] + input.extra;
+        let distance = cost[trails.index(&input.end).unwrap()].unwrap();
         format!("{distance}")
     }
     fn pt2(&self, input: &str) -> String {
         let input = Input::from(input);
+        let hike = Hike::from(&input);
         let shared = AtomicU32::new(0);
         let threads = thread::available_parallelism().unwrap().get();
 
         // Seed each worker thread with a starting state
+        let mut seen = BitSet::new(hike.edges.len());
+        seen.insert(hike.start);
         let mut seeds = VecDeque::new();
-        seeds.push_back((input.start, 1 << input.start, 0));
+        seeds.push_back((hike.start, seen, 0));
 
         while seeds.len() < threads {
             let Some((from, seen, cost)) = seeds.pop_front() else {
                 break;
             };
 
-            if from == input.end {
+            if from == hike.end {
                 shared.fetch_max(cost, Ordering::Relaxed);
                 continue;
             }
 
-            let mut nodes = input.undirected[from] & !seen;
-
-            while nodes > 0 {
-                let to = nodes.trailing_zeros() as usize;
-                let mask = 1 << to;
-                nodes ^= mask;
-
-                seeds.push_back((to, seen | mask, cost + input.weight[from][to]));
+            for &(to, weight) in &hike.edges[from] {
+                if !seen.contains(to) {
+                    let mut seen = seen.clone();
+                    seen.insert(to);
+                    seeds.push_back((to, seen, cost + weight));
+                }
             }
         }
 
         // Use as many cores as possible to parallelize the remaining search.
         thread::scope(|scope| {
             for start in &seeds {
-                scope.spawn(|| worker(&input, &shared, start));
+                scope.spawn(|| worker(&hike, &shared, start));
             }
         });
 
-        let distance = shared.load(Ordering::Relaxed) + input.extra;
+        let distance = shared.load(Ordering::Relaxed) + hike.extra;
 
         format!("{distance}")
     }
 }
 
-fn worker(input: &Input, shared: &AtomicU32, start: &(usize, u64, u32)) {
-    let (from, seen, cost) = *start;
-    let result = dfs(input, from, seen);
+fn worker(hike: &Hike, shared: &AtomicU32, start: &(usize, BitSet, u32)) {
+    let (from, seen, cost) = start;
+    let result = dfs(hike, *from, &mut seen.clone());
     shared.fetch_max(result + cost, Ordering::Relaxed);
 }
 
-fn dfs(input: &Input, from: usize, seen: u64) -> u32 {
-    if from == input.end {
+fn dfs(hike: &Hike, from: usize, seen: &mut BitSet) -> u32 {
+    if from == hike.end {
         return 0;
     }
 
-    let mut nodes = input.undirected[from] & !seen;
     let mut result = 0;
 
-    while nodes > 0 {
-        let to = nodes.trailing_zeros() as usize;
-        let mask = 1 << to;
-        nodes ^= mask;
-
-        result = result.max(input.weight[from][to] + dfs(input, to, seen | mask));
+    for &(to, weight) in &hike.edges[from] {
+        if !seen.contains(to) {
+            seen.insert(to);
+            result = result.max(weight + dfs(hike, to, seen));
+            seen.remove(to);
+        }
     }
 
     result
@@ -106,151 +103,141 @@ pub(crate) fn input() -> &'static str {
     include_str!("day23-input.txt").trim()
 }
 
+/// The trails, with the start and end tiles.
 pub struct Input {
-    start: usize,
-    end: usize,
-    extra: u32,
-    directed: [u64; 36],
-    undirected: [u64; 36],
-    weight: [[u32; 36]; 36],
+    grid: Grid<u8>,
+    start: Coordinate<usize>,
+    end: Coordinate<usize>,
 }
 
 impl From<&str> for Input {
     fn from(value: &str) -> Self {
-        let mut grid = Grid::from(value);
-        let width = grid.width;
-        let height = grid.height;
-
-        // Modify edge of grid to remove the need for boundary checks.
-        let start = grid.points[..grid.width]
-            .iter()
-            .enumerate()
-            .find(|(i, _p)| grid.points[*i] != b'#')
-            .map(|(i, _)| grid.coord(i))
-            .unwrap();
-        let end = grid
-            .points
-            .iter()
-            .enumerate()
-            .skip(grid.points.len() - grid.width)
-            .find(|(i, _p)| grid.points[*i] != b'#')
-            .map(|(i, _)| grid.coord(i))
-            .unwrap();
-
-        // Modify edge of grid to remove the need for boundary checks.
-        grid[&start] = b'#';
-        grid[&end] = b'#';
-
-        // Move start and end away from edge.
-        let start = Coordinate(start.0, start.1 + 1);
-        let end = Coordinate(end.0, end.1 - 1);
-
-        // Points of interest are start, end and junctions.
-        grid[&start] = b'P';
-        grid[&end] = b'P';
-
-        let mut poi: HashMap<Coordinate<usize>, usize> = HashMap::new();
-
-        poi.insert(start, 0);
-        poi.insert(end, 1);
-
-        for y in 1..height - 1 {
-            for x in 1..width - 1 {
-                let position = Coordinate(x, y);
-
-                if grid[&position] != b'#' {
-                    let neighbors = OFFSETS
-                        .iter()
-                        .map(|&o| position + o)
-                        .filter(|n| grid[n] != b'#')
-                        .count();
-                    if neighbors > 2 {
-                        grid[&position] = b'P';
-                        poi.insert(position, poi.len());
-                    }
-                }
-            }
-        }
+        let grid = Grid::from(value);
+        let open = |i: usize| grid.points[i] != b'#';
+        let start = grid.coord((0..grid.width).find(|&i| open(i)).unwrap());
+        let end = grid.coord(
+            (grid.points.len() - grid.width..grid.points.len())
+                .find(|&i| open(i))
+                .unwrap(),
+        );
+        Input { grid, start, end }
+    }
+}
 
-        // BFS to find distances between POIs.
-        let mut todo = VecDeque::new();
-        let mut directed: [u64; 36] = [0; 36];
-        let mut undirected: [u64; 36] = [0; 36];
-        let mut weight = [[0; 36]; 36];
-
-        for (&start, &from) in &poi {
-            todo.push_back((start, 0, true));
-            grid[&start] = b'#';
-
-            while let Some((position, cost, forward)) = todo.pop_front() {
-                for direction in OFFSETS {
-                    let next = position + direction;
-                    match grid[&next] {
-                        b'#' => (),
-                        b'P' => {
-                            let to = poi[&next];
-
-                            if forward {
-                                directed[from] |= 1 << to;
-                            } else {
-                                directed[to] |= 1 << from;
-                            }
-
-                            undirected[from] |= 1 << to;
-                            undirected[to] |= 1 << from;
-
-                            weight[from][to] = cost + 1;
-                            weight[to][from] = cost + 1;
-                        }
-                        b'.' => {
-                            todo.push_back((next, cost + 1, forward));
-                            grid[&next] = b'#';
-                        }
-                        _ => {
-                            let terrain = grid[&next];
-                            let same = direction
-                                == match terrain {
-                                    b'^' => UP,
-                                    b'>' => RIGHT,
-                                    b'v' => DOWN,
-                                    b'<' => LEFT,
-                                    _ => panic!("Unknown terrain: '{terrain}'"),
-                                };
-                            todo.push_back((next, cost + 1, forward && same));
-                            grid[&next] = b'#';
-                        }
-                    }
+impl Input {
+    /// The trails contracted to the junctions between them, where a slope can
+    /// only be crossed in the direction it points.
+    fn directed(&self) -> Graph<Coordinate<usize>> {
+        self.trails(|position, next, direction| {
+            [position, next]
+                .iter()
+                .all(|&c| slope(self.grid[&c]).is_none_or(|s| s == direction))
+        })
+    }
+
+    /// The trails contracted to the junctions between them, ignoring slopes.
+    fn undirected(&self) -> Graph<Coordinate<usize>> {
+        self.trails(|_, _, _| true)
+    }
+
+    /// Links every pair of adjacent open tiles in each direction `passable`
+    /// allows, then contracts the corridors between junctions.
+    fn trails(
+        &self,
+        passable: impl Fn(Coordinate<usize>, Coordinate<usize>, Direction) -> bool,
+    ) -> Graph<Coordinate<usize>> {
+        let grid = &self.grid;
+        let mut trails = Graph::new();
+        for position in (0..grid.points.len())
+            .filter(|&i| grid.points[i] != b'#')
+            .map(|i| grid.coord(i))
+        {
+            for (direction, back) in [(RIGHT, LEFT), (DOWN, UP)] {
+                let Coordinate(x, y) = position;
+                if (direction == RIGHT && x + 1 == grid.width)
+                    || (direction == DOWN && y + 1 == grid.height)
+                {
+                    continue;
+                }
+                let next = position + direction;
+                if grid[&next] == b'#' {
+                    continue;
+                }
+                if passable(position, next, direction) {
+                    trails.add_edge(position, next, 1);
+                }
+                if passable(position, next, back) {
+                    trails.add_edge(next, position, 1);
                 }
             }
         }
 
-        // Compress
-        let start = undirected[0].trailing_zeros() as usize;
-        let end = undirected[1].trailing_zeros() as usize;
-        let extra = 2 + weight[0][start] + weight[1][end];
-
-        // Heuristic
-        let mut mask = 0;
+        trails.contract(|c| *c == self.start || *c == self.end)
+    }
+}
 
-        for (i, edges) in undirected.iter().enumerate() {
-            if edges.count_ones() < 4 {
-                mask |= 1 << i;
-            }
-        }
+fn slope(terrain: u8) -> Option<Direction> {
+    match terrain {
+        b'^' => Some(UP),
+        b'>' => Some(RIGHT),
+        b'v' => Some(DOWN),
+        b'<' => Some(LEFT),
+        _ => None,
+    }
+}
 
-        for (i, edges) in undirected.iter_mut().enumerate() {
-            if edges.count_ones() < 4 {
-                *edges = (*edges & !mask) | directed[i];
-            }
-        }
+/// The junctions as indices into `edges`, ready for an exhaustive search.
+struct Hike {
+    start: usize,
+    end: usize,
+    /// The fixed distance walked before `start` and after `end`.
+    extra: u32,
+    edges: Vec<Vec<(usize, u32)>>,
+}
 
-        Input {
-            start,
-            end,
-            extra,
-            directed,
-            undirected,
-            weight,
+impl From<&Input> for Hike {
+    fn from(input: &Input) -> Self {
+        let graph = &input.undirected();
+        let directed = &input.directed();
+
+        // The start and end tiles each lead to a single junction, so begin and
+        // finish the search there instead.
+        let [(start, to_start)] = graph.neighbours(graph.index(&input.start).unwrap()) else {
+            panic!("start should lead to a single junction");
+        };
+        let [(end, to_end)] = graph.neighbours(graph.index(&input.end).unwrap()) else {
+            panic!("end should lead to a single junction");
+        };
+
+        // Heuristic: junctions on the perimeter have fewer than four neighbours.
+        // Walking one the wrong way round cuts off the way to the end, so only
+        // follow those edges downhill.
+        let perimeter = |i: usize| graph.neighbours(i).len() < 4;
+        let edges = (0..graph.node_count())
+            .map(|from| {
+                graph
+                    .neighbours(from)
+                    .iter()
+                    .copied()
+                    .filter(|&(to, _)| {
+                        !perimeter(from)
+                            || !perimeter(to)
+                            || directed
+                                .index(graph.key(from))
+                                .zip(directed.index(graph.key(to)))
+                                .is_some_and(|(f, t)| {
+                                    directed.neighbours(f).iter().any(|&(n, _)| n == t)
+                                })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Hike {
+            start: *start,
+            end: *end,
+            extra: to_start + to_end,
+            edges,
         }
     }
 }
@@ -288,13 +275,23 @@ mod tests {
     #[test]
     fn test_start() {
         let input = Input::from(example_input());
-        assert_eq!(3, input.start);
+        assert_eq!(Coordinate(1, 0), input.start);
     }
 
     #[test]
     fn test_goal() {
         let input = Input::from(example_input());
-        assert_eq!(8, input.end);
+        assert_eq!(Coordinate(21, 22), input.end);
+    }
+
+    #[test]
+    fn test_junctions() {
+        let input = Input::from(example_input());
+        let (directed, undirected) = (input.directed(), input.undirected());
+        assert_eq!(9, undirected.node_count());
+        assert_eq!(9, directed.node_count());
+        assert_eq!(12, directed.edges().count());
+        assert_eq!(24, undirected.edges().count());
     }
 
     #[test]
@@ -1,5 +1,9 @@
 use std::ops::{Add, Div, Mul, RangeInclusive, Sub};
 
+use num::ToPrimitive;
+
+use crate::libs::parse;
+use crate::libs::rational::{rational, solve, Rational};
 use crate::problem::Solver;
 pub struct Day {}
 
@@ -29,12 +33,15 @@ impl Hailstones<i128> {
 
         for (i, hailstone) in self.0.iter().enumerate() {
             for other in self.0.iter().skip(i + 1) {
-                if let Some((cross_x, cross_y, cross_time_1, cross_time_2)) = hailstone.cross(other)
-                {
-                    if test_area.contains(&cross_x)
-                        && test_area.contains(&cross_y)
-                        && cross_time_1 > 0
-                        && cross_time_2 > 0
+                if let Some((x, y, denominator)) = hailstone.cross(other) {
+                    let inside = |value: i128| {
+                        test_area.start() * denominator <= value
+                            && value <= test_area.end() * denominator
+                    };
+                    if inside(x)
+                        && inside(y)
+                        && hailstone.reaches(x, denominator)
+                        && other.reaches(x, denominator)
                     {
                         crosses += 1;
                     }
@@ -45,27 +52,33 @@ impl Hailstones<i128> {
         crosses
     }
     fn rock_position(&self) -> i128 {
-        // Threes stones, relative
+        // For every hailstone i, (p - p_i) x (v - v_i) = 0. Subtracting the
+        // equations of two hailstones cancels the non-linear p x v term:
+        // p x (v_j - v_i) + (p_j - p_i) x v = p_j x v_j - p_i x v_i
         let h0 = self.0[0];
-        let h1 = self.0[1] - h0;
-        let h2 = self.0[2] - h0;
-
-        // Find plane for seond and third hailstone
-        let q = h1.velocity.cross(h1.position).gcd();
-        let r = h2.velocity.cross(h2.position).gcd();
-        let s = q.cross(r).gcd();
-
-        let t1 = (h1.position.y * s.x - h1.position.x * s.y)
-            / (h1.velocity.x * s.y - h1.velocity.y * s.x);
-        let t2 = (h2.position.y * s.x - h2.position.x * s.y)
-            / (h2.velocity.x * s.y - h2.velocity.y * s.x);
-        assert!(t1 != t2);
-
-        let a = h0.position.add(h1.position).sum();
-        let b = h0.position.add(h2.position).sum();
-        let c = h1.velocity.sub(h2.velocity).sum();
+        let mut a: Vec<Vec<Rational>> = vec![];
+        let mut b: Vec<Rational> = vec![];
+        for hj in &self.0[1..3] {
+            let d = hj.velocity - h0.velocity;
+            let e = hj.position - h0.position;
+            let rhs = hj.position.cross(hj.velocity) - h0.position.cross(h0.velocity);
+            for (row, value) in [
+                ([0, d.z, -d.y, 0, -e.z, e.y], rhs.x),
+                ([-d.z, 0, d.x, e.z, 0, -e.x], rhs.y),
+                ([d.y, -d.x, 0, -e.y, e.x, 0], rhs.z),
+            ] {
+                a.push(row.into_iter().map(rational).collect());
+                b.push(rational(value));
+            }
+        }
 
-        (t2 * a - t1 * b + t2 * t1 * c) / (t2 - t1)
+        let rock = solve(a, b).expect("hailstones should determine a unique rock");
+        let position: Rational = rock[..3].iter().sum();
+        assert!(
+            position.is_integer(),
+            "non-integer rock position: {position}"
+        );
+        position.to_integer().to_i128().unwrap()
     }
 }
 
@@ -76,25 +89,6 @@ impl Vector3d<i128> {
         let z = self.x * other.y - self.y * other.x;
         Self { x, y, z }
     }
-    fn gcd(&self) -> Self {
-        let gcd = [self.x, self.y, self.z].into_iter().reduce(gcd).unwrap();
-        let x = self.x / gcd;
-        let y = self.y / gcd;
-        let z = self.z / gcd;
-        Self { x, y, z }
-    }
-    fn sum(self) -> i128 {
-        self.x + self.y + self.z
-    }
-}
-fn gcd(a: i128, b: i128) -> i128 {
-    let (mut a, mut b) = (a, b);
-
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-
-    a
 }
 
 struct Hailstones<T>(Vec<Hailstone<T>>);
@@ -105,20 +99,24 @@ struct Hailstone<T> {
     velocity: Vector3d<T>,
 }
 impl Hailstone<i128> {
-    fn cross(&self, other: &Hailstone<i128>) -> Option<(i128, i128, i128, i128)> {
+    /// Where the paths cross, as `x` and `y` numerators over a shared
+    /// positive denominator, so that no precision is lost to division.
+    fn cross(&self, other: &Hailstone<i128>) -> Option<(i128, i128, i128)> {
         if self.a() * other.b() == self.b() * other.a() {
             // parallel
             None
         } else {
-            let x = (self.c() * other.b() - other.c() * self.b())
-                / (self.a() * other.b() - other.a() * self.b());
-            let y = (other.c() * self.a() - self.c() * other.a())
-                / (self.a() * other.b() - other.a() * self.b());
-            let t1 = (x - self.position.x) / self.velocity.x;
-            let t2 = (x - other.position.x) / other.velocity.x;
-            Some((x, y, t1, t2))
+            let determinant = self.a() * other.b() - other.a() * self.b();
+            let sign = determinant.signum();
+            let x = (self.c() * other.b() - other.c() * self.b()) * sign;
+            let y = (other.c() * self.a() - self.c() * other.a()) * sign;
+            Some((x, y, determinant * sign))
         }
     }
+    /// Whether the hailstone gets to `x / denominator` in the future.
+    fn reaches(&self, x: i128, denominator: i128) -> bool {
+        (x - self.position.x * denominator).signum() == self.velocity.x.signum()
+    }
     fn a(&self) -> i128 {
         self.velocity.y
     }
@@ -154,14 +152,8 @@ impl From<&str> for Hailstone<i128> {
 }
 impl From<&str> for Vector3d<i128> {
     fn from(value: &str) -> Self {
-        let xyz = value
-            .split(',')
-            .map(|n| {
-                n.trim()
-                    .parse::<i128>()
-                    .unwrap_or_else(|_| panic!("Invalid digit in value '{value}': '{n}'"))
-            })
-            .collect::<Vec<_>>();
+        let xyz: Vec<i128> =
+            parse::list(value, ',').unwrap_or_else(|e| panic!("Invalid value '{value}': {e}"));
         Self {
             x: xyz[0],
             y: xyz[1],
@@ -170,16 +162,6 @@ impl From<&str> for Vector3d<i128> {
     }
 }
 
-impl Sub<Hailstone<i128>> for Hailstone<i128> {
-    type Output = Self;
-    fn sub(self, other: Hailstone<i128>) -> Self::Output {
-        Self {
-            position: self.position - other.position,
-            velocity: self.velocity - other.velocity,
-        }
-    }
-}
-
 impl Add<Vector3d<i128>> for Vector3d<i128> {
     type Output = Self;
     fn add(self, other: Vector3d<i128>) -> Self::Output {
@@ -224,6 +206,7 @@ impl Sub<Vector3d<i128>> for Vector3d<i128> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::libs::rational::fraction;
 
     fn example_input() -> &'static str {
         "19, 13, 30 @ -2,  1, -2
@@ -243,9 +226,17 @@ mod tests {
         let h2 = hailstones.0[2];
         let h3 = hailstones.0[3];
 
-        assert_eq!(Some((14, 15, 2, 4)), h0.cross(&h1));
-        assert_eq!(Some((11, 16, 4, 4)), h0.cross(&h2));
-        assert_eq!(Some((6, 19, 6, 6)), h0.cross(&h3));
+        let crossing = |a: &Hailstone<i128>, b: &Hailstone<i128>| {
+            let (x, y, denominator) = a.cross(b).unwrap();
+            assert!(a.reaches(x, denominator) && b.reaches(x, denominator));
+            (fraction(x, denominator), fraction(y, denominator))
+        };
+        assert_eq!((fraction(43, 3), fraction(46, 3)), crossing(&h0, &h1));
+        assert_eq!((fraction(35, 3), fraction(50, 3)), crossing(&h0, &h2));
+        assert_eq!((fraction(31, 5), fraction(97, 5)), crossing(&h0, &h3));
+        // Hailstone A crossed hailstone E's path in the past.
+        let (x, _, denominator) = h0.cross(&hailstones.0[4]).unwrap();
+        assert!(!h0.reaches(x, denominator));
 
         assert_eq!(2, hailstones.cross(lower_bound..=upper_bound));
     }
@@ -1,6 +1,20 @@
 use std::fmt::Display;
 use std::ops::{Add, Index, IndexMut};
 
+pub mod bits;
+pub mod cycle;
+pub mod graph;
+pub mod interval;
+pub mod matcher;
+pub mod memo;
+pub mod parse;
+pub mod polygon;
+pub mod polynomial;
+pub mod rational;
+pub mod region;
+pub mod render;
+pub mod tiled;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Coordinate<T>(pub T, pub T);
 
@@ -57,32 +71,22 @@ impl Display for Coordinate<usize> {
     }
 }
 
-pub fn shoelace(points: &[Coordinate<isize>]) -> isize {
-    let area = points
-        .windows(2)
-        .map(|w| w[0].0 * w[1].1 - w[0].1 * w[1].0)
-        .sum::<isize>()
-        .abs()
-        / 2;
-
-    let perimeter: isize = points
-        .windows(2)
-        .map(|w| {
-            let dx: isize = w[1].0.abs_diff(w[0].0).try_into().unwrap();
-            let dy: isize = w[1].1.abs_diff(w[0].1).try_into().unwrap();
-            dx + dy
-        })
-        .sum::<isize>();
-
-    area + perimeter / 2 + 1
-}
-
 pub struct Grid<T> {
     pub points: Vec<T>,
     pub width: usize,
     pub height: usize,
 }
 
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            points: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+}
+
 impl<T> Grid<T> {
     pub fn coord(&self, index: usize) -> Coordinate<usize> {
         Coordinate(index % self.width, index / self.width)
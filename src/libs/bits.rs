@@ -0,0 +1,514 @@
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use super::{Coordinate, Direction, DOWN, LEFT, RIGHT, UP};
+
+const WORD: usize = u64::BITS as usize;
+
+/// A fixed-length set of bits backed by `u64` words.
+///
+/// Bits past `len` in the last word are always kept clear.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    /// A set of length `len` with every bit set.
+    pub fn full(len: usize) -> Self {
+        let mut set = Self {
+            words: vec![u64::MAX; len.div_ceil(WORD)],
+            len,
+        };
+        set.trim();
+        set
+    }
+
+    fn trim(&mut self) {
+        let used = self.len % WORD;
+        if used != 0 {
+            *self.words.last_mut().unwrap() &= (1 << used) - 1;
+        }
+    }
+
+    /// The number of bits, set or not.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.len && self.words[index / WORD] & (1 << (index % WORD)) != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, index: usize) {
+        assert!(index < self.len, "bit {index} out of range {}", self.len);
+        self.words[index / WORD] |= 1 << (index % WORD);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, index: usize) {
+        assert!(index < self.len, "bit {index} out of range {}", self.len);
+        self.words[index / WORD] &= !(1 << (index % WORD));
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    None
+                } else {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(i * WORD + bit)
+                }
+            })
+        })
+    }
+
+    /// Moves every bit `n` positions towards higher indices, dropping bits
+    /// that fall off the end.
+    pub fn shift_up(&self, n: usize) -> Self {
+        let mut shifted = Self::new(self.len);
+        let (words, bits) = (n / WORD, n % WORD);
+        for i in words..self.words.len() {
+            let mut word = self.words[i - words] << bits;
+            if bits > 0 && i > words {
+                word |= self.words[i - words - 1] >> (WORD - bits);
+            }
+            shifted.words[i] = word;
+        }
+        shifted.trim();
+        shifted
+    }
+
+    /// Moves every bit `n` positions towards lower indices, dropping bits
+    /// that fall off the start.
+    pub fn shift_down(&self, n: usize) -> Self {
+        let mut shifted = Self::new(self.len);
+        let (words, bits) = (n / WORD, n % WORD);
+        for i in 0..self.words.len().saturating_sub(words) {
+            let mut word = self.words[i + words] >> bits;
+            if bits > 0 && i + words + 1 < self.words.len() {
+                word |= self.words[i + words + 1] << (WORD - bits);
+            }
+            shifted.words[i] = word;
+        }
+        shifted
+    }
+
+    /// The word of bits starting at `index`, padded with zeros past the end.
+    fn word_at(&self, index: usize) -> u64 {
+        let (word, bit) = (index / WORD, index % WORD);
+        let low = self.words[word] >> bit;
+        match self.words.get(word + 1) {
+            Some(high) if bit > 0 => low | high << (WORD - bit),
+            _ => low,
+        }
+    }
+
+    /// Overwrites the bits under `mask`, shifted to start at `index`.
+    fn write_word(&mut self, index: usize, value: u64, mask: u64) {
+        let (word, bit) = (index / WORD, index % WORD);
+        self.words[word] = self.words[word] & !(mask << bit) | (value & mask) << bit;
+        if bit > 0 && mask >> (WORD - bit) != 0 {
+            let high = &mut self.words[word + 1];
+            *high = *high & !(mask >> (WORD - bit)) | (value & mask) >> (WORD - bit);
+        }
+    }
+
+    /// The `len` bits starting at `start`, as a set of their own.
+    pub fn slice(&self, start: usize, len: usize) -> Self {
+        assert!(start + len <= self.len, "slice out of range {}", self.len);
+        let mut slice = Self::new(len);
+        for (i, word) in slice.words.iter_mut().enumerate() {
+            *word = self.word_at(start + i * WORD);
+        }
+        slice.trim();
+        slice
+    }
+
+    /// Overwrites the bits from `start` onwards with `bits`.
+    pub fn set_slice(&mut self, start: usize, bits: &Self) {
+        assert!(
+            start + bits.len <= self.len,
+            "slice out of range {}",
+            self.len
+        );
+        for (i, &word) in bits.words.iter().enumerate() {
+            let width = (bits.len - i * WORD).min(WORD);
+            let mask = u64::MAX >> (WORD - width);
+            self.write_word(start + i * WORD, word, mask);
+        }
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(self.len, other.len, "bit sets differ in length");
+        let mut result = Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            len: self.len,
+        };
+        result.trim();
+        result
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+}
+
+impl BitOr for &BitSet {
+    type Output = BitSet;
+    fn bitor(self, other: Self) -> Self::Output {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &BitSet {
+    type Output = BitSet;
+    fn bitand(self, other: Self) -> Self::Output {
+        self.intersection(other)
+    }
+}
+
+impl BitXor for &BitSet {
+    type Output = BitSet;
+    fn bitxor(self, other: Self) -> Self::Output {
+        self.symmetric_difference(other)
+    }
+}
+
+impl Not for &BitSet {
+    type Output = BitSet;
+    fn not(self) -> Self::Output {
+        let mut result = BitSet {
+            words: self.words.iter().map(|w| !w).collect(),
+            len: self.len,
+        };
+        result.trim();
+        result
+    }
+}
+
+/// A two dimensional grid of bits stored row by row in a single `BitSet`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitGrid {
+    bits: BitSet,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            bits: BitSet::new(width * height),
+            width,
+            height,
+        }
+    }
+
+    /// A grid with the bits set wherever `predicate` holds for the cell.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        predicate: impl Fn(Coordinate<usize>) -> bool,
+    ) -> Self {
+        let mut grid = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if predicate(Coordinate(x, y)) {
+                    grid.insert(&Coordinate(x, y));
+                }
+            }
+        }
+        grid
+    }
+
+    fn index(&self, coordinate: &Coordinate<usize>) -> usize {
+        coordinate.0 + coordinate.1 * self.width
+    }
+
+    pub fn contains(&self, coordinate: &Coordinate<usize>) -> bool {
+        coordinate.0 < self.width
+            && coordinate.1 < self.height
+            && self.bits.contains(self.index(coordinate))
+    }
+
+    pub fn insert(&mut self, coordinate: &Coordinate<usize>) {
+        self.bits.insert(self.index(coordinate));
+    }
+
+    pub fn remove(&mut self, coordinate: &Coordinate<usize>) {
+        self.bits.remove(self.index(coordinate));
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Coordinate<usize>> + '_ {
+        self.bits
+            .iter()
+            .map(|i| Coordinate(i % self.width, i / self.width))
+    }
+
+    pub fn row(&self, y: usize) -> BitSet {
+        self.bits.slice(y * self.width, self.width)
+    }
+
+    pub fn set_row(&mut self, y: usize, row: &BitSet) {
+        assert_eq!(self.width, row.len(), "row length differs from grid width");
+        self.bits.set_slice(y * self.width, row);
+    }
+
+    fn column(&self, x: usize) -> BitSet {
+        let mut column = BitSet::new(self.bits.len());
+        (0..self.height).for_each(|y| column.insert(x + y * self.width));
+        column
+    }
+
+    /// Moves every set bit one cell in `direction`, dropping bits that leave the grid.
+    pub fn shift(&self, direction: Direction) -> Self {
+        let bits = match direction {
+            UP => self.bits.shift_down(self.width),
+            DOWN => self.bits.shift_up(self.width),
+            RIGHT => self.bits.shift_up(1).difference(&self.column(0)),
+            LEFT => self
+                .bits
+                .shift_down(1)
+                .difference(&self.column(self.width - 1)),
+            _ => panic!("Unhandled direction: {direction}"),
+        };
+        Self {
+            bits,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(&BitSet, &BitSet) -> BitSet) -> Self {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "bit grids differ in size"
+        );
+        Self {
+            bits: f(&self.bits, &other.bits),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_with(other, BitSet::union)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip_with(other, BitSet::intersection)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip_with(other, BitSet::difference)
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+    fn bitor(self, other: Self) -> Self::Output {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+    fn bitand(self, other: Self) -> Self::Output {
+        self.intersection(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(len: usize, bits: &[usize]) -> BitSet {
+        let mut set = BitSet::new(len);
+        bits.iter().for_each(|&b| set.insert(b));
+        set
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut bits = BitSet::new(130);
+        bits.insert(0);
+        bits.insert(64);
+        bits.insert(129);
+        assert!(bits.contains(64));
+        assert!(!bits.contains(65));
+        assert!(!bits.contains(500));
+        assert_eq!(3, bits.count_ones());
+        bits.remove(64);
+        assert_eq!(vec![0, 129], bits.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_full_and_not() {
+        let full = BitSet::full(70);
+        assert_eq!(70, full.count_ones());
+        assert!((!&full).is_empty());
+        assert_eq!(68, (!&set(70, &[3, 69])).count_ones());
+    }
+
+    #[test]
+    fn test_shift() {
+        let bits = set(200, &[0, 63, 100, 199]);
+        assert_eq!(
+            vec![1, 64, 101],
+            bits.shift_up(1).iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![65, 128, 165],
+            bits.shift_up(65).iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![62, 99, 198],
+            bits.shift_down(1).iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![35, 134],
+            bits.shift_down(65).iter().collect::<Vec<_>>()
+        );
+        assert!(bits.shift_up(200).is_empty());
+        assert!(bits.shift_down(200).is_empty());
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = set(100, &[1, 2, 3, 70]);
+        let b = set(100, &[3, 4, 70, 99]);
+        assert_eq!(set(100, &[1, 2, 3, 4, 70, 99]), &a | &b);
+        assert_eq!(set(100, &[3, 70]), &a & &b);
+        assert_eq!(set(100, &[1, 2]), a.difference(&b));
+        assert_eq!(set(100, &[1, 2, 4, 99]), &a ^ &b);
+        assert!(set(100, &[3]).is_subset(&a));
+        assert!(!b.is_subset(&a));
+    }
+
+    #[test]
+    fn test_slice() {
+        let bits = set(200, &[0, 5, 63, 64, 100, 130, 199]);
+        for (start, len) in [
+            (0, 200),
+            (5, 60),
+            (60, 70),
+            (63, 2),
+            (100, 100),
+            (199, 1),
+            (7, 0),
+        ] {
+            let expected: Vec<usize> = bits
+                .iter()
+                .filter(|&b| (start..start + len).contains(&b))
+                .map(|b| b - start)
+                .collect();
+            let slice = bits.slice(start, len);
+            assert_eq!(len, slice.len());
+            assert_eq!(
+                expected,
+                slice.iter().collect::<Vec<_>>(),
+                "{start}..+{len}"
+            );
+
+            let mut copy = BitSet::full(200);
+            copy.set_slice(start, &slice);
+            let outside_or_set = (0..200)
+                .filter(|b| !(start..start + len).contains(b) || bits.contains(*b))
+                .collect::<Vec<_>>();
+            assert_eq!(outside_or_set, copy.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_grid_shift() {
+        let mut grid = BitGrid::new(3, 3);
+        grid.insert(&Coordinate(0, 0));
+        grid.insert(&Coordinate(2, 1));
+        grid.insert(&Coordinate(1, 2));
+
+        let coordinates = |g: BitGrid| g.iter().collect::<Vec<_>>();
+        assert_eq!(
+            vec![Coordinate(2, 0), Coordinate(1, 1)],
+            coordinates(grid.shift(UP))
+        );
+        assert_eq!(
+            vec![Coordinate(0, 1), Coordinate(2, 2)],
+            coordinates(grid.shift(DOWN))
+        );
+        assert_eq!(
+            vec![Coordinate(1, 0), Coordinate(2, 2)],
+            coordinates(grid.shift(RIGHT))
+        );
+        assert_eq!(
+            vec![Coordinate(1, 1), Coordinate(0, 2)],
+            coordinates(grid.shift(LEFT))
+        );
+    }
+
+    #[test]
+    fn test_grid_rows() {
+        let mut grid = BitGrid::from_fn(4, 3, |c| c.0 == c.1);
+        assert_eq!(set(4, &[1]), grid.row(1));
+        grid.set_row(1, &set(4, &[0, 3]));
+        assert_eq!(
+            vec![
+                Coordinate(0, 0),
+                Coordinate(0, 1),
+                Coordinate(3, 1),
+                Coordinate(2, 2)
+            ],
+            grid.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(4, grid.count_ones());
+    }
+}
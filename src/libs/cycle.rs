@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use num::Integer;
+
+/// The shape of the sequence `x0, f(x0), f(f(x0)), ...`: after `start` steps
+/// the states repeat every `length` steps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The smallest step count that reaches the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Detects the cycle by remembering every visited state.
+pub fn find<S: Eq + Hash>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut state = initial;
+    for i in 0.. {
+        if let Some(&start) = seen.get(&state) {
+            return Cycle {
+                start,
+                length: i - start,
+            };
+        }
+        let next = step(&state);
+        seen.insert(state, i);
+        state = next;
+    }
+    unreachable!()
+}
+
+/// Returns the state after `n` steps, jumping ahead as soon as a cycle is found.
+pub fn state_at<S: Clone + Eq + Hash>(initial: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut history: Vec<S> = vec![];
+    let mut state = initial;
+    for i in 0..n {
+        if let Some(&start) = seen.get(&state) {
+            let cycle = Cycle {
+                start,
+                length: i - start,
+            };
+            return history.swap_remove(cycle.reduce(n));
+        }
+        let next = step(&state);
+        seen.insert(state.clone(), i);
+        history.push(state);
+        state = next;
+    }
+    state
+}
+
+/// Floyd's tortoise and hare; uses constant memory instead of a lookup table.
+pub fn floyd<S: Eq>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Brent's algorithm; like `floyd` but with fewer calls to `step`.
+pub fn brent<S: Clone + Eq>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = (0..length).fold(initial, |state, _| step(&state));
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// The earliest time at which all `(offset, period)` events coincide, where
+/// each event happens at `offset, offset + period, offset + 2 * period, ...`.
+///
+/// Uses the generalised Chinese remainder theorem, so periods need not be
+/// coprime. Returns `None` when the events never line up.
+pub fn align(events: &[(usize, usize)]) -> Option<usize> {
+    let (mut residue, mut modulus) = (0_i128, 1_i128);
+    for &(offset, period) in events {
+        assert!(period > 0, "period must be positive");
+        let (offset, period) = (offset as i128, period as i128);
+        let gcd = modulus.extended_gcd(&period);
+        let difference = offset - residue;
+        if difference % gcd.gcd != 0 {
+            return None;
+        }
+        // Solve `residue + modulus * k = offset (mod period)` for `k`.
+        let reduced = period / gcd.gcd;
+        let k = (difference / gcd.gcd % reduced * gcd.x).rem_euclid(reduced);
+        residue += modulus * k;
+        modulus *= reduced;
+        residue = residue.rem_euclid(modulus);
+    }
+
+    // No event happens before its offset.
+    let latest = events.iter().map(|&(offset, _)| offset as i128).max()?;
+    let time = if residue >= latest {
+        residue
+    } else {
+        residue + Integer::div_ceil(&(latest - residue), &modulus) * modulus
+    };
+    usize::try_from(time).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(x: &u32) -> u32 {
+        (x * x + 1) % 255
+    }
+
+    fn brute_force(initial: u32, n: usize) -> u32 {
+        (0..n).fold(initial, |x, _| step(&x))
+    }
+
+    #[test]
+    fn test_variants_agree() {
+        for initial in 0..255 {
+            let expected = find(initial, step);
+            assert_eq!(expected, floyd(initial, step), "initial: {initial}");
+            assert_eq!(expected, brent(initial, step), "initial: {initial}");
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        // 3 -> 10 -> 101 -> 2 -> 5 -> 26 -> 167 -> 95 -> 101
+        assert_eq!(
+            Cycle {
+                start: 2,
+                length: 6
+            },
+            find(3, step)
+        );
+    }
+
+    #[test]
+    fn test_pure_cycle() {
+        assert_eq!(
+            Cycle {
+                start: 0,
+                length: 5
+            },
+            find(0, |x| (x + 1) % 5)
+        );
+    }
+
+    #[test]
+    fn test_state_at() {
+        for n in 0..100 {
+            assert_eq!(brute_force(3, n), state_at(3, step, n), "n: {n}");
+        }
+        let cycle = find(3, step);
+        assert_eq!(
+            brute_force(3, cycle.reduce(1_000_000_000)),
+            state_at(3, step, 1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_align() {
+        assert_eq!(Some(23), align(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some(10), align(&[(2, 4), (4, 6)]));
+        assert_eq!(None, align(&[(1, 4), (2, 6)]));
+        assert_eq!(None, align(&[]));
+    }
+
+    #[test]
+    fn test_align_starts_after_offsets() {
+        // t = 1 (mod 12), but the first event only happens at 10.
+        assert_eq!(Some(13), align(&[(10, 3), (5, 4)]));
+        // Periods starting at their own length reduce to the lcm.
+        assert_eq!(Some(12), align(&[(4, 4), (6, 6)]));
+        assert_eq!(Some(0), align(&[(0, 4), (0, 6)]));
+    }
+
+    #[test]
+    fn test_align_brute_force() {
+        for a in 0..8 {
+            for b in 0..8 {
+                let events = [(a, 3), (b, 6), (1, 2)];
+                let expected = (0..100).find(|&t| {
+                    events
+                        .iter()
+                        .all(|&(offset, period)| t >= offset && (t - offset) % period == 0)
+                });
+                assert_eq!(expected, align(&events), "events: {events:?}");
+            }
+        }
+    }
+}
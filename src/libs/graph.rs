@@ -0,0 +1,325 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+/// A weighted, directed graph stored as adjacency lists. Nodes are interned:
+/// every distinct key is assigned a dense index on first use.
+///
+/// Undirected graphs are represented by adding each edge in both directions.
+#[derive(Clone, Debug)]
+pub struct Graph<K> {
+    keys: Vec<K>,
+    indices: HashMap<K, usize>,
+    edges: Vec<Vec<(usize, u32)>>,
+}
+
+impl<K> Default for Graph<K> {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            indices: HashMap::new(),
+            edges: vec![],
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> Graph<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The index of `key`, adding it as a node if it is new.
+    pub fn intern(&mut self, key: K) -> usize {
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+        let index = self.keys.len();
+        self.keys.push(key.clone());
+        self.indices.insert(key, index);
+        self.edges.push(vec![]);
+        index
+    }
+
+    pub fn index(&self, key: &K) -> Option<usize> {
+        self.indices.get(key).copied()
+    }
+
+    pub fn key(&self, index: usize) -> &K {
+        &self.keys[index]
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn add_edge(&mut self, from: K, to: K, weight: u32) {
+        let (from, to) = (self.intern(from), self.intern(to));
+        self.edges[from].push((to, weight));
+    }
+
+    pub fn add_undirected_edge(&mut self, a: K, b: K, weight: u32) {
+        self.add_edge(a.clone(), b.clone(), weight);
+        self.add_edge(b, a, weight);
+    }
+
+    pub fn neighbours(&self, index: usize) -> &[(usize, u32)] {
+        &self.edges[index]
+    }
+
+    /// Every edge as `(from, to, weight)`.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, u32)> + '_ {
+        self.edges
+            .iter()
+            .enumerate()
+            .flat_map(|(from, edges)| edges.iter().map(move |&(to, weight)| (from, to, weight)))
+    }
+
+    /// Replaces corridors of nodes with exactly two neighbours by single
+    /// edges carrying the summed weight. Nodes for which `keep` holds are
+    /// never removed. In a directed graph a corridor only becomes an edge in
+    /// the directions its edges allow it to be walked.
+    pub fn contract(&self, keep: impl Fn(&K) -> bool) -> Self {
+        let mut adjacent = vec![vec![]; self.node_count()];
+        for (from, to, _) in self.edges() {
+            adjacent[from].push(to);
+            adjacent[to].push(from);
+        }
+        for neighbours in &mut adjacent {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+        let junction = |i: usize| keep(&self.keys[i]) || adjacent[i].len() != 2;
+        let mut contracted = Self::new();
+        for from in (0..self.node_count()).filter(|&i| junction(i)) {
+            contracted.intern(self.keys[from].clone());
+            'corridor: for &(first, weight) in &self.edges[from] {
+                let (mut previous, mut current, mut total) = (from, first, weight);
+                while !junction(current) && current != from {
+                    let Some(&(next, weight)) =
+                        self.edges[current].iter().find(|(n, _)| *n != previous)
+                    else {
+                        continue 'corridor;
+                    };
+                    (previous, current, total) = (current, next, total + weight);
+                }
+                if junction(current) {
+                    contracted.add_edge(self.keys[from].clone(), self.keys[current].clone(), total);
+                }
+            }
+        }
+        contracted
+    }
+
+    /// The nodes ordered so that every edge points forwards, or `None` when
+    /// the graph has a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        let mut incoming = vec![0; self.node_count()];
+        self.edges().for_each(|(_, to, _)| incoming[to] += 1);
+
+        let mut todo: VecDeque<usize> = (0..self.node_count())
+            .filter(|&i| incoming[i] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.node_count());
+        while let Some(node) = todo.pop_front() {
+            order.push(node);
+            for &(to, _) in &self.edges[node] {
+                incoming[to] -= 1;
+                if incoming[to] == 0 {
+                    todo.push_back(to);
+                }
+            }
+        }
+
+        (order.len() == self.node_count()).then_some(order)
+    }
+
+    /// The strongly connected components, using Kosaraju's algorithm.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        // First pass: order the nodes by the time their depth first search finishes.
+        let mut visited = vec![false; self.node_count()];
+        let mut finished = Vec::with_capacity(self.node_count());
+        for root in 0..self.node_count() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+            while let Some((node, edge)) = stack.pop() {
+                if let Some(&(next, _)) = self.edges[node].get(edge) {
+                    stack.push((node, edge + 1));
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push((next, 0));
+                    }
+                } else {
+                    finished.push(node);
+                }
+            }
+        }
+
+        // Second pass: collect components on the transposed graph.
+        let mut transposed = vec![vec![]; self.node_count()];
+        self.edges()
+            .for_each(|(from, to, _)| transposed[to].push(from));
+        let mut component = vec![None; self.node_count()];
+        let mut components = vec![];
+        for &root in finished.iter().rev() {
+            if component[root].is_some() {
+                continue;
+            }
+            let mut members = vec![root];
+            component[root] = Some(components.len());
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                for &previous in &transposed[node] {
+                    if component[previous].is_none() {
+                        component[previous] = Some(components.len());
+                        members.push(previous);
+                        stack.push(previous);
+                    }
+                }
+            }
+            members.sort_unstable();
+            components.push(members);
+        }
+        components
+    }
+}
+
+impl<K: Clone + Display + Eq + Hash> Graph<K> {
+    /// The graph in Graphviz DOT format, with weights as edge labels.
+    pub fn dot(&self) -> String {
+        let mut out = String::from("digraph {\n");
+        for key in &self.keys {
+            writeln!(out, "  \"{key}\";").unwrap();
+        }
+        for (from, to, weight) in self.edges() {
+            writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label={weight}];",
+                self.keys[from], self.keys[to]
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_edges<K: Clone + Eq + Hash + Ord>(graph: &Graph<K>) -> Vec<(K, K, u32)> {
+        let mut edges: Vec<_> = graph
+            .edges()
+            .map(|(from, to, weight)| (graph.key(from).clone(), graph.key(to).clone(), weight))
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", 1);
+        graph.add_edge("b", "c", 2);
+        graph.add_edge("a", "c", 3);
+        assert_eq!(3, graph.node_count());
+        assert_eq!(Some(0), graph.index(&"a"));
+        assert_eq!(Some(2), graph.index(&"c"));
+        assert_eq!(None, graph.index(&"d"));
+        assert_eq!(&"b", graph.key(1));
+        assert_eq!(&[(1, 1), (2, 3)], graph.neighbours(0));
+    }
+
+    #[test]
+    fn test_contract() {
+        // a - x - y - b, with a second branch b - z - c
+        let mut graph = Graph::new();
+        graph.add_undirected_edge("a", "x", 1);
+        graph.add_undirected_edge("x", "y", 2);
+        graph.add_undirected_edge("y", "b", 3);
+        graph.add_undirected_edge("b", "z", 4);
+        graph.add_undirected_edge("z", "c", 5);
+        graph.add_undirected_edge("b", "d", 6);
+
+        let contracted = graph.contract(|_| false);
+        assert_eq!(4, contracted.node_count());
+        assert_eq!(
+            vec![
+                ("a", "b", 6),
+                ("b", "a", 6),
+                ("b", "c", 9),
+                ("b", "d", 6),
+                ("c", "b", 9),
+                ("d", "b", 6),
+            ],
+            sorted_edges(&contracted)
+        );
+
+        let kept = graph.contract(|&k| k == "x");
+        assert_eq!(Some(1), kept.index(&"x"));
+        assert!(kept
+            .edges()
+            .any(|(f, t, w)| (*kept.key(f), *kept.key(t), w) == ("x", "b", 5)));
+    }
+
+    #[test]
+    fn test_contract_directed() {
+        // a -> x <-> y -> b, where the corridor cannot be walked back to a.
+        let mut graph = Graph::new();
+        graph.add_edge("a", "x", 1);
+        graph.add_undirected_edge("x", "y", 2);
+        graph.add_edge("y", "b", 3);
+        graph.add_edge("b", "c", 4);
+        graph.add_edge("b", "d", 5);
+        assert_eq!(
+            vec![("a", "b", 6), ("b", "c", 4), ("b", "d", 5)],
+            sorted_edges(&graph.contract(|_| false))
+        );
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let mut graph = Graph::new();
+        graph.add_edge("shirt", "tie", 0);
+        graph.add_edge("tie", "jacket", 0);
+        graph.add_edge("trousers", "shoes", 0);
+        graph.add_edge("trousers", "belt", 0);
+        graph.add_edge("belt", "jacket", 0);
+        let order = graph.topological_sort().unwrap();
+        let position = |key| order.iter().position(|&i| graph.key(i) == &key).unwrap();
+        for (from, to, _) in graph.edges() {
+            assert!(position(*graph.key(from)) < position(*graph.key(to)));
+        }
+
+        graph.add_edge("jacket", "shirt", 0);
+        assert_eq!(None, graph.topological_sort());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let mut graph = Graph::new();
+        graph.add_edge(0, 1, 0);
+        graph.add_edge(1, 2, 0);
+        graph.add_edge(2, 0, 0);
+        graph.add_edge(2, 3, 0);
+        graph.add_edge(3, 4, 0);
+        graph.add_edge(4, 3, 0);
+        graph.add_edge(4, 5, 0);
+        let mut components = graph.strongly_connected_components();
+        components.sort();
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4], vec![5]], components);
+    }
+
+    #[test]
+    fn test_dot() {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", 7);
+        assert_eq!(
+            "digraph {\n  \"a\";\n  \"b\";\n  \"a\" -> \"b\" [label=7];\n}\n",
+            graph.dot()
+        );
+    }
+}
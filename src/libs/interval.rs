@@ -0,0 +1,385 @@
+use std::ops::Range;
+
+use num::PrimInt;
+
+/// A set of integers stored as sorted, disjoint and non-adjacent half-open ranges.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: vec![] }
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::zero(), |acc, r| acc + (r.end - r.start))
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|r| r.end - T::one())
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(i).is_some_and(|r| r.start <= *value)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        // Ranges that overlap or touch the new one are merged into it.
+        let lo = self.ranges.partition_point(|r| r.end < start);
+        let hi = self.ranges.partition_point(|r| r.start <= end);
+        if lo < hi {
+            start = start.min(self.ranges[lo].start);
+            end = end.max(self.ranges[hi - 1].end);
+        }
+        self.ranges.splice(lo..hi, [start..end]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let lo = self.ranges.partition_point(|r| r.end <= range.start);
+        let hi = self.ranges.partition_point(|r| r.start < range.end);
+        if lo == hi {
+            return;
+        }
+        let mut remaining = Vec::with_capacity(2);
+        if self.ranges[lo].start < range.start {
+            remaining.push(self.ranges[lo].start..range.start);
+        }
+        if self.ranges[hi - 1].end > range.end {
+            remaining.push(range.end..self.ranges[hi - 1].end);
+        }
+        self.ranges.splice(lo..hi, remaining);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        other.ranges.iter().for_each(|r| union.insert(r.clone()));
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        other
+            .ranges
+            .iter()
+            .for_each(|r| difference.remove(r.clone()));
+        difference
+    }
+
+    /// Splits the set into the values below `at` and the values at or above it.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let (mut below, mut above) = (vec![], vec![]);
+        for range in &self.ranges {
+            if range.end <= at {
+                below.push(range.clone());
+            } else if range.start >= at {
+                above.push(range.clone());
+            } else {
+                below.push(range.start..at);
+                above.push(at..range.end);
+            }
+        }
+        (Self { ranges: below }, Self { ranges: above })
+    }
+
+    /// Moves every value in the set by `offset`.
+    pub fn shift(&self, offset: T) -> Self {
+        Self {
+            ranges: self
+                .ranges
+                .iter()
+                .map(|r| (r.start + offset)..(r.end + offset))
+                .collect(),
+        }
+    }
+}
+
+impl<T: PrimInt> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: PrimInt> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|r| set.insert(r));
+        set
+    }
+}
+
+/// A mapping from disjoint half-open ranges to values.
+///
+/// Inserting a range overwrites whatever it overlaps, and adjacent ranges
+/// holding equal values are coalesced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntervalMap<T, V> {
+    entries: Vec<(Range<T>, V)>,
+}
+
+impl<T, V> Default for IntervalMap<T, V> {
+    fn default() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+impl<T: PrimInt, V: Clone + PartialEq> IntervalMap<T, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Range<T>, &V)> {
+        self.entries.iter().map(|(r, v)| (r, v))
+    }
+
+    pub fn get(&self, key: &T) -> Option<&V> {
+        let i = self.entries.partition_point(|(r, _)| r.end <= *key);
+        self.entries
+            .get(i)
+            .filter(|(r, _)| r.start <= *key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+        let lo = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        let hi = self.entries.partition_point(|(r, _)| r.start < range.end);
+        let mut replacement = Vec::with_capacity(3);
+        if lo < hi && self.entries[lo].0.start < range.start {
+            let (first, v) = &self.entries[lo];
+            replacement.push((first.start..range.start, v.clone()));
+        }
+        replacement.push((range.clone(), value));
+        if lo < hi && self.entries[hi - 1].0.end > range.end {
+            let (last, v) = &self.entries[hi - 1];
+            replacement.push((range.end..last.end, v.clone()));
+        }
+        self.entries.splice(lo..hi, replacement);
+        self.coalesce();
+    }
+
+    fn coalesce(&mut self) {
+        let mut entries: Vec<(Range<T>, V)> = Vec::with_capacity(self.entries.len());
+        for (range, value) in self.entries.drain(..) {
+            match entries.last_mut() {
+                Some((last, v)) if last.end == range.start && *v == value => last.end = range.end,
+                _ => entries.push((range, value)),
+            }
+        }
+        self.entries = entries;
+    }
+
+    /// The set of keys that have a value.
+    pub fn domain(&self) -> IntervalSet<T> {
+        self.entries.iter().map(|(r, _)| r.clone()).collect()
+    }
+}
+
+impl<T: PrimInt> IntervalMap<T, T> {
+    /// Treats the values as offsets and moves each part of `set` by the offset
+    /// of the range it falls in. Values outside the domain pass through unchanged.
+    pub fn translate(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut translated = set.difference(&self.domain());
+        for (range, offset) in &self.entries {
+            let covered = set.intersection(&IntervalSet::from(range.clone()));
+            translated = translated.union(&covered.shift(*offset));
+        }
+        translated
+    }
+}
+
+impl<T: PrimInt, V: Clone + PartialEq> FromIterator<(Range<T>, V)> for IntervalMap<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        iter.into_iter().for_each(|(r, v)| map.insert(r, v));
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIVERSE: u32 = 8;
+
+    fn from_mask(mask: u32) -> IntervalSet<i32> {
+        (0..UNIVERSE)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| i32::try_from(i).unwrap())
+            .map(|i| i..i + 1)
+            .collect()
+    }
+
+    fn to_mask(set: &IntervalSet<i32>) -> u32 {
+        (0..UNIVERSE)
+            .filter(|&i| set.contains(&i32::try_from(i).unwrap()))
+            .fold(0, |acc, i| acc | (1 << i))
+    }
+
+    fn is_canonical(set: &IntervalSet<i32>) -> bool {
+        set.ranges().iter().all(|r| !r.is_empty())
+            && set.ranges().windows(2).all(|w| w[0].end < w[1].start)
+    }
+
+    #[test]
+    fn test_set_operations_exhaustive() {
+        for a in 0..(1 << UNIVERSE) {
+            let set_a = from_mask(a);
+            assert!(is_canonical(&set_a));
+            assert_eq!(a, to_mask(&set_a));
+            assert_eq!(a.count_ones(), u32::try_from(set_a.len()).unwrap());
+            for b in 0..(1 << UNIVERSE) {
+                let set_b = from_mask(b);
+                let union = set_a.union(&set_b);
+                let intersection = set_a.intersection(&set_b);
+                let difference = set_a.difference(&set_b);
+                assert!(is_canonical(&union));
+                assert!(is_canonical(&intersection));
+                assert!(is_canonical(&difference));
+                assert_eq!(a | b, to_mask(&union));
+                assert_eq!(a & b, to_mask(&intersection));
+                assert_eq!(a & !b, to_mask(&difference));
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_at_exhaustive() {
+        for a in 0..(1 << UNIVERSE) {
+            let set = from_mask(a);
+            for at in 0..=UNIVERSE {
+                let (below, above) = set.split_at(i32::try_from(at).unwrap());
+                assert!(is_canonical(&below));
+                assert!(is_canonical(&above));
+                assert_eq!(a & ((1 << at) - 1), to_mask(&below));
+                assert_eq!(a & !((1 << at) - 1), to_mask(&above));
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert_coalesces() {
+        let set: IntervalSet<i64> = [0..3, 5..8, 3..5, 10..12].into_iter().collect();
+        assert_eq!(&[0..8, 10..12], set.ranges());
+        assert_eq!(Some(0), set.min());
+        assert_eq!(Some(11), set.max());
+        assert_eq!(10, set.len());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = IntervalSet::from(0..10);
+        set.remove(3..5);
+        assert_eq!(&[0..3, 5..10], set.ranges());
+        set.remove(-5..1);
+        set.remove(9..20);
+        assert_eq!(&[1..3, 5..9], set.ranges());
+        set.remove(2..6);
+        assert_eq!(&[1..2, 6..9], set.ranges());
+    }
+
+    #[test]
+    fn test_shift() {
+        let set: IntervalSet<i64> = [0..2, 4..6].into_iter().collect();
+        assert_eq!(&[10..12, 14..16], set.shift(10).ranges());
+        assert_eq!(&[-3..-1, 1..3], set.shift(-3).ranges());
+    }
+
+    #[test]
+    fn test_map_insert_overwrites() {
+        let mut map = IntervalMap::new();
+        map.insert(0..10, 'a');
+        map.insert(3..5, 'b');
+        assert_eq!(
+            vec![(&(0..3), &'a'), (&(3..5), &'b'), (&(5..10), &'a')],
+            map.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&'a'), map.get(&0));
+        assert_eq!(Some(&'b'), map.get(&4));
+        assert_eq!(None, map.get(&10));
+        map.insert(2..7, 'c');
+        assert_eq!(
+            vec![(&(0..2), &'a'), (&(2..7), &'c'), (&(7..10), &'a')],
+            map.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_map_coalesces_equal_values() {
+        let map: IntervalMap<i32, char> = [(0..3, 'a'), (5..8, 'a'), (3..5, 'a'), (8..9, 'b')]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            vec![(&(0..8), &'a'), (&(8..9), &'b')],
+            map.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(IntervalSet::from(0..9), map.domain());
+    }
+
+    #[test]
+    fn test_map_translate() {
+        let map: IntervalMap<i64, i64> = [(98..100, -48), (50..98, 2)].into_iter().collect();
+        assert_eq!(
+            &[0..2, 50..52],
+            map.translate(&IntervalSet::from(0..2).union(&IntervalSet::from(98..100)))
+                .ranges()
+        );
+        assert_eq!(
+            &[40..50, 52..55],
+            map.translate(&IntervalSet::from(40..53)).ranges()
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::iter;
+
+/// A word found in a haystack, spanning the bytes `start..end`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Match<V> {
+    pub start: usize,
+    pub end: usize,
+    pub value: V,
+}
+
+#[derive(Debug)]
+struct Node<V> {
+    children: HashMap<u8, usize>,
+    fail: usize,
+    /// The length and value of the word ending in this node.
+    output: Option<(usize, V)>,
+    /// The nearest node along the failure links that ends a word.
+    dictionary: Option<usize>,
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Self {
+            children: HashMap::new(),
+            fail: 0,
+            output: None,
+            dictionary: None,
+        }
+    }
+}
+
+/// Finds every occurrence of a set of words at once, using the Aho-Corasick
+/// automaton. Matches may overlap, as `eight` and `two` do in `eightwo`.
+#[derive(Debug)]
+pub struct Matcher<V> {
+    nodes: Vec<Node<V>>,
+    longest: usize,
+}
+
+impl<V: Copy> Matcher<V> {
+    /// Builds the automaton for a word to value table. Empty words are
+    /// ignored; for duplicate words the last value wins.
+    pub fn new(table: &[(&str, V)]) -> Self {
+        let mut nodes = vec![Node::new()];
+        for &(word, value) in table.iter().filter(|(word, _)| !word.is_empty()) {
+            let mut node = 0;
+            for byte in word.bytes() {
+                node = match nodes[node].children.get(&byte) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::new());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(byte, child);
+                        child
+                    }
+                };
+            }
+            nodes[node].output = Some((word.len(), value));
+        }
+
+        // Failure links point to the longest proper suffix that is also in
+        // the trie, so they are set breadth first.
+        let mut todo: VecDeque<usize> = nodes[0].children.values().copied().collect();
+        while let Some(node) = todo.pop_front() {
+            let children: Vec<(u8, usize)> = nodes[node]
+                .children
+                .iter()
+                .map(|(&byte, &child)| (byte, child))
+                .collect();
+            for (byte, child) in children {
+                let mut fail = nodes[node].fail;
+                while fail != 0 && !nodes[fail].children.contains_key(&byte) {
+                    fail = nodes[fail].fail;
+                }
+                let fail = nodes[fail].children.get(&byte).copied().unwrap_or(0);
+                nodes[child].fail = fail;
+                nodes[child].dictionary = if nodes[fail].output.is_some() {
+                    Some(fail)
+                } else {
+                    nodes[fail].dictionary
+                };
+                todo.push_back(child);
+            }
+        }
+
+        let longest = table.iter().map(|(word, _)| word.len()).max().unwrap_or(0);
+        Self { nodes, longest }
+    }
+
+    fn step(&self, mut node: usize, byte: u8) -> usize {
+        loop {
+            if let Some(&child) = self.nodes[node].children.get(&byte) {
+                return child;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// The words ending in `node`, longest first.
+    fn outputs(&self, node: usize, end: usize) -> impl Iterator<Item = Match<V>> + '_ {
+        let first = if self.nodes[node].output.is_some() {
+            Some(node)
+        } else {
+            self.nodes[node].dictionary
+        };
+        iter::successors(first, |&n| self.nodes[n].dictionary).map(move |n| {
+            let (len, value) = self.nodes[n].output.unwrap();
+            Match {
+                start: end - len,
+                end,
+                value,
+            }
+        })
+    }
+
+    /// Every match, including overlapping ones, ordered by where they end.
+    pub fn find_iter<'a>(&'a self, haystack: &'a str) -> impl Iterator<Item = Match<V>> + 'a {
+        let mut stream = self.stream();
+        haystack.bytes().flat_map(move |byte| stream.push(byte))
+    }
+
+    /// The match that starts first, preferring the longest on a tie.
+    pub fn first(&self, haystack: &str) -> Option<Match<V>> {
+        let mut first: Option<Match<V>> = None;
+        for m in self.find_iter(haystack) {
+            // Nothing ending this late can start before the current best.
+            if first.is_some_and(|f| m.end > f.start + self.longest) {
+                break;
+            }
+            if first.is_none_or(|f| (m.start, Reverse(m.end)) < (f.start, Reverse(f.end))) {
+                first = Some(m);
+            }
+        }
+        first
+    }
+
+    /// The match that starts last, preferring the longest on a tie.
+    pub fn last(&self, haystack: &str) -> Option<Match<V>> {
+        self.find_iter(haystack).max_by_key(|m| (m.start, m.end))
+    }
+
+    /// Matches input that arrives in chunks, as when reading a large file.
+    pub fn stream(&self) -> Stream<'_, V> {
+        Stream {
+            matcher: self,
+            node: 0,
+            offset: 0,
+        }
+    }
+}
+
+/// Matching state carried between chunks, so that words split across a
+/// chunk boundary are still found. Offsets count from the first chunk.
+pub struct Stream<'a, V> {
+    matcher: &'a Matcher<V>,
+    node: usize,
+    offset: usize,
+}
+
+impl<'a, V: Copy> Stream<'a, V> {
+    /// The matches ending at `byte`.
+    pub fn push(&mut self, byte: u8) -> impl Iterator<Item = Match<V>> + 'a {
+        self.node = self.matcher.step(self.node, byte);
+        self.offset += 1;
+        self.matcher.outputs(self.node, self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGITS: &[(&str, u32)] = &[
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("eight", 8),
+        ("nine", 9),
+        ("1", 1),
+        ("2", 2),
+    ];
+
+    fn values<V: Copy>(matches: impl Iterator<Item = Match<V>>) -> Vec<V> {
+        matches.map(|m| m.value).collect()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let matcher = Matcher::new(DIGITS);
+        assert_eq!(vec![8, 2], values(matcher.find_iter("eightwo")));
+        assert_eq!(vec![2, 1], values(matcher.find_iter("xtwone3")));
+        assert_eq!(vec![9, 8], values(matcher.find_iter("nineight")));
+        assert_eq!(
+            vec![
+                Match {
+                    start: 0,
+                    end: 5,
+                    value: 8
+                },
+                Match {
+                    start: 4,
+                    end: 7,
+                    value: 2
+                }
+            ],
+            matcher.find_iter("eightwo").collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let matcher = Matcher::new(DIGITS);
+        assert_eq!(Some(2), matcher.first("xtwone3four").map(|m| m.value));
+        assert_eq!(Some(1), matcher.last("xtwone3four").map(|m| m.value));
+        assert_eq!(Some(8), matcher.first("aeightwo").map(|m| m.value));
+        assert_eq!(Some(2), matcher.last("aeightwo").map(|m| m.value));
+        assert_eq!(None, matcher.first("abc"));
+        assert_eq!(None, matcher.last(""));
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let roman = Matcher::new(&[("I", 1), ("IV", 4), ("V", 5), ("IX", 9), ("X", 10)]);
+        assert_eq!(vec![1, 4, 5, 10], values(roman.find_iter("IVX")));
+        assert_eq!(Some(4), roman.first("IVX").map(|m| m.value));
+
+        let german = Matcher::new(&[("eins", 1), ("zwei", 2), ("drei", 3)]);
+        assert_eq!(vec![3, 1, 2], values(german.find_iter("dreinszwei")));
+    }
+
+    #[test]
+    fn test_suffix_words() {
+        let matcher = Matcher::new(&[("she", 0), ("he", 1), ("hers", 2), ("his", 3)]);
+        assert_eq!(vec![0, 1, 2], values(matcher.find_iter("ushers")));
+    }
+
+    #[test]
+    fn test_stream() {
+        let matcher = Matcher::new(DIGITS);
+        let haystack = "two1nine\neightwothree\nabcone2threexyz";
+        let expected: Vec<_> = matcher.find_iter(haystack).collect();
+        for split in 0..haystack.len() {
+            let (head, tail) = haystack.split_at(split);
+            let mut stream = matcher.stream();
+            let mut streamed = vec![];
+            for chunk in [head, tail] {
+                for byte in chunk.bytes() {
+                    streamed.extend(stream.push(byte));
+                }
+            }
+            assert_eq!(expected, streamed, "split at: {split}");
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+}
+
+/// A cache of computed values, optionally bounded in size. When full, the
+/// oldest entry is evicted first.
+pub struct Cache<K, V> {
+    entries: HashMap<K, V>,
+    order: VecDeque<K>,
+    capacity: Option<usize>,
+    stats: Stats,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Cache<K, V> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: None,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn bounded(capacity: usize) -> Self {
+        assert!(capacity > 0, "a bounded cache needs room for an entry");
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let value = self.entries.get(key).cloned();
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.entries.insert(key.clone(), value).is_some() {
+            return;
+        }
+        // Only a bounded cache needs to know which entry is oldest.
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        self.order.push_back(key);
+        if self.entries.len() > capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    /// Makes room for at least `additional` more entries up front, sparing
+    /// the rehashing as the cache grows.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Default for Cache<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A recursive function with its results cached by argument.
+///
+/// The function receives a callback to use for its recursive calls, so those
+/// go through the cache as well:
+///
+/// ```ignore
+/// let mut fib = Memoized::new(|fib: &mut dyn FnMut(u64) -> u64, n| {
+///     if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+/// });
+/// assert_eq!(12_586_269_025, fib.call(50));
+/// ```
+pub struct Memoized<K, V, F> {
+    cache: Cache<K, V>,
+    f: F,
+}
+
+impl<K, V, F> Memoized<K, V, F>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    pub fn new(f: F) -> Self {
+        Self {
+            cache: Cache::new(),
+            f,
+        }
+    }
+
+    pub fn bounded(capacity: usize, f: F) -> Self {
+        Self {
+            cache: Cache::bounded(capacity),
+            f,
+        }
+    }
+
+    /// Makes room for at least `additional` more cached results.
+    pub fn reserve(&mut self, additional: usize) {
+        self.cache.reserve(additional);
+    }
+
+    pub fn call(&mut self, key: K) -> V {
+        Self::call_cached(&mut self.cache, &self.f, key)
+    }
+
+    fn call_cached(cache: &mut Cache<K, V>, f: &F, key: K) -> V {
+        if let Some(value) = cache.get(&key) {
+            return value;
+        }
+        let value = f(&mut |k| Self::call_cached(cache, f, k), key.clone());
+        cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.cache.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fibonacci() {
+        let mut fib = Memoized::new(
+            |fib: &mut dyn FnMut(u64) -> u64, n| {
+                if n < 2 {
+                    n
+                } else {
+                    fib(n - 1) + fib(n - 2)
+                }
+            },
+        );
+        assert_eq!(12_586_269_025, fib.call(50));
+        assert_eq!(
+            Stats {
+                hits: 48,
+                misses: 51,
+                evictions: 0
+            },
+            fib.stats()
+        );
+        assert_eq!(55, fib.call(10));
+        assert_eq!(49, fib.stats().hits);
+    }
+
+    #[test]
+    fn test_multiple_arguments() {
+        // Number of lattice paths through an m x n grid.
+        let mut paths = Memoized::new(|paths: &mut dyn FnMut((u32, u32)) -> u64, (m, n)| {
+            if m == 0 || n == 0 {
+                1
+            } else {
+                paths((m - 1, n)) + paths((m, n - 1))
+            }
+        });
+        assert_eq!(137_846_528_820, paths.call((20, 20)));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut cache = Cache::bounded(2);
+        cache.insert(1, 'a');
+        cache.insert(2, 'b');
+        cache.insert(3, 'c');
+        assert_eq!(2, cache.len());
+        assert_eq!(None, cache.get(&1));
+        assert_eq!(Some('b'), cache.get(&2));
+        assert_eq!(Some('c'), cache.get(&3));
+        assert_eq!(
+            Stats {
+                hits: 2,
+                misses: 1,
+                evictions: 1
+            },
+            cache.stats()
+        );
+    }
+
+    #[test]
+    fn test_bounded_memoized_is_still_correct() {
+        let mut fib = Memoized::bounded(
+            3,
+            |fib: &mut dyn FnMut(u64) -> u64, n| {
+                if n < 2 {
+                    n
+                } else {
+                    fib(n - 1) + fib(n - 2)
+                }
+            },
+        );
+        assert_eq!(6765, fib.call(20));
+        assert!(fib.stats().evictions > 0);
+    }
+}
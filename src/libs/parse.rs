@@ -0,0 +1,310 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A parse failure, located by 1-based line and column.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: 1,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn on_line(self, line: usize) -> Self {
+        Self { line, ..self }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for String {
+    fn from(value: ParseError) -> Self {
+        value.to_string()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+fn column(s: &str, offset: usize) -> usize {
+    s[..offset].chars().count() + 1
+}
+
+fn parse_at<T: FromStr>(s: &str, start: usize, end: usize) -> Result<T>
+where
+    T::Err: Display,
+{
+    s[start..end].parse().map_err(|e| {
+        ParseError::new(
+            column(s, start),
+            format!("invalid value '{}': {e}", &s[start..end]),
+        )
+    })
+}
+
+/// Every integer in `s`, in order. A `-` directly in front of the digits is
+/// taken as a sign unless it follows another digit, as in `1-3`.
+pub fn ints<T: FromStr>(s: &str) -> Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    let bytes = s.as_bytes();
+    let mut values = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let mut start = i;
+        if start > 0
+            && bytes[start - 1] == b'-'
+            && (start < 2 || !bytes[start - 2].is_ascii_digit())
+        {
+            start -= 1;
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        values.push(parse_at(s, start, i)?);
+    }
+    Ok(values)
+}
+
+/// The blocks of `input` separated by one or more blank lines.
+pub fn sections(input: &str) -> Vec<&str> {
+    let mut sections = vec![];
+    let mut start = None;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(start) = start.take() {
+                sections.push(input[start..offset].trim_end());
+            }
+        } else if start.is_none() {
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        sections.push(input[start..].trim_end());
+    }
+    sections
+}
+
+/// Splits `line` at the first `separator`, trimming both halves.
+pub fn key_value<'a>(line: &'a str, separator: &str) -> Result<(&'a str, &'a str)> {
+    line.split_once(separator)
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .ok_or_else(|| ParseError::new(1, format!("expected '{separator}' in '{line}'")))
+}
+
+/// Parses every `separator`-delimited item of `s`, ignoring surrounding whitespace.
+pub fn list<T: FromStr>(s: &str, separator: char) -> Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    let mut offset = 0;
+    s.split(separator)
+        .map(|item| {
+            let start = offset + (item.len() - item.trim_start().len());
+            let end = offset + item.trim_end().len();
+            offset += item.len() + separator.len_utf8();
+            parse_at(s, start, end)
+        })
+        .collect()
+}
+
+/// Parses every line of `input`, attaching the line number to any error.
+pub fn lines<T>(input: &str, parse: impl Fn(&str) -> Result<T>) -> Result<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+/// A cursor over a single line, for parsers that consume it piece by piece.
+#[derive(Debug)]
+pub struct Scanner<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, position: 0 }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    /// The 1-based column of the next character.
+    pub fn column(&self) -> usize {
+        column(self.input, self.position)
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.column(), message)
+    }
+
+    pub fn skip_whitespace(&mut self) -> &mut Self {
+        self.position = self.input.len() - self.rest().trim_start().len();
+        self
+    }
+
+    pub fn tag(&mut self, tag: &str) -> Result<&mut Self> {
+        if !self.rest().starts_with(tag) {
+            return Err(self.error(format!("expected '{tag}'")));
+        }
+        self.position += tag.len();
+        Ok(self)
+    }
+
+    fn take_while(&mut self, f: impl Fn(usize, char) -> bool) -> &'a str {
+        let start = self.position;
+        let len = self
+            .rest()
+            .char_indices()
+            .find(|&(i, c)| !f(i, c))
+            .map_or(self.rest().len(), |(i, _)| i);
+        self.position += len;
+        &self.input[start..self.position]
+    }
+
+    /// A run of letters and digits.
+    pub fn word(&mut self) -> Result<&'a str> {
+        match self.take_while(|_, c| c.is_alphanumeric()) {
+            "" => Err(self.error("expected a word")),
+            word => Ok(word),
+        }
+    }
+
+    /// An optionally signed integer.
+    pub fn number<T: FromStr>(&mut self) -> Result<T>
+    where
+        T::Err: Display,
+    {
+        let start = self.position;
+        let digits = self.take_while(|i, c| c.is_ascii_digit() || (i == 0 && c == '-'));
+        if !digits.bytes().any(|b| b.is_ascii_digit()) {
+            self.position = start;
+            return Err(self.error("expected a number"));
+        }
+        parse_at(self.input, start, self.position)
+    }
+
+    /// Everything up to `delimiter`, consuming the delimiter as well.
+    pub fn until(&mut self, delimiter: &str) -> Result<&'a str> {
+        let len = self
+            .rest()
+            .find(delimiter)
+            .ok_or_else(|| self.error(format!("expected '{delimiter}'")))?;
+        let taken = &self.rest()[..len];
+        self.position += len + delimiter.len();
+        Ok(taken)
+    }
+
+    pub fn end(&self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(format!("unexpected '{}'", self.rest())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(
+            Ok(vec![19, 13, 30, -2, 1, -2]),
+            ints::<i64>("19, 13, 30 @ -2,  1, -2")
+        );
+        assert_eq!(Ok(vec![1, 3]), ints::<u8>("1-3"));
+        assert_eq!(Ok(vec![787, 2655]), ints::<u16>("{x=787,m=2655}"));
+        assert_eq!(Ok(vec![]), ints::<u8>("seed-to-soil map:"));
+        let error = ints::<u8>("1 2 300").unwrap_err();
+        assert_eq!((1, 5), (error.line, error.column));
+    }
+
+    #[test]
+    fn test_sections() {
+        assert_eq!(
+            vec!["a\nb", "c", "d"],
+            sections("a\nb\n\nc\n  \n\n d\n")
+                .into_iter()
+                .map(str::trim)
+                .collect::<Vec<_>>()
+        );
+        assert!(sections("\n\n").is_empty());
+    }
+
+    #[test]
+    fn test_key_value() {
+        assert_eq!(Ok(("Card 1", "41 48")), key_value("Card 1: 41 48", ":"));
+        assert_eq!(
+            "1:1: expected ' = ' in 'AAA'",
+            key_value("AAA", " = ").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(Ok(vec![1, 1, 3]), list::<u8>("1,1,3", ','));
+        assert_eq!(Ok(vec![4, 2]), list::<u8>(" 4 ,  2", ','));
+        let error = list::<u8>("1, x", ',').unwrap_err();
+        assert_eq!(4, error.column);
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(Ok(vec![vec![1, 2], vec![3]]), lines("1 2\n3", ints::<u8>));
+        let error = lines("1\n2\n-3", |line| list::<u8>(line, ',')).unwrap_err();
+        assert_eq!(
+            "3:1: invalid value '-3': invalid digit found in string",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_scanner() {
+        let mut scanner = Scanner::new("AAA = (BBB, -12)");
+        assert_eq!(Ok("AAA"), scanner.word());
+        scanner.tag(" = (").unwrap();
+        assert_eq!(Ok("BBB"), scanner.until(","));
+        scanner.skip_whitespace();
+        assert_eq!(Ok(-12), scanner.number::<i8>());
+        assert_eq!(Ok(()), scanner.tag(")").and_then(|s| s.end()));
+
+        let mut scanner = Scanner::new("a:b");
+        scanner.word().unwrap();
+        let error = scanner.tag("=").unwrap_err();
+        assert_eq!((2, "expected '='"), (error.column, error.message.as_str()));
+        assert_eq!(2, scanner.number::<u8>().unwrap_err().column);
+        assert_eq!(":b", scanner.rest());
+        assert!(scanner.end().is_err());
+    }
+}
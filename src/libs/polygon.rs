@@ -0,0 +1,198 @@
+use num::integer::gcd;
+
+use super::Coordinate;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    Degenerate,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// A simple polygon on the integer lattice.
+///
+/// The vertices may be given either closed (first point repeated at the end)
+/// or unclosed; the closing edge is always implied.
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    vertices: Vec<Coordinate<isize>>,
+}
+
+impl From<&[Coordinate<isize>]> for Polygon {
+    fn from(points: &[Coordinate<isize>]) -> Self {
+        let mut vertices = points.to_vec();
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+}
+
+impl From<&Vec<Coordinate<isize>>> for Polygon {
+    fn from(points: &Vec<Coordinate<isize>>) -> Self {
+        Self::from(points.as_slice())
+    }
+}
+
+impl Polygon {
+    fn edges(&self) -> impl Iterator<Item = (&Coordinate<isize>, &Coordinate<isize>)> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    /// Twice the signed area; positive when the vertices run counter-clockwise
+    /// in a y-up frame (clockwise on screen, where y grows downwards).
+    fn signed_double_area(&self) -> isize {
+        self.edges().map(|(a, b)| a.0 * b.1 - a.1 * b.0).sum()
+    }
+
+    /// Twice the enclosed area, which is always an integer for lattice polygons.
+    pub fn double_area(&self) -> isize {
+        self.signed_double_area().abs()
+    }
+
+    /// The enclosed area, rounded down for polygons with half-integer area.
+    pub fn area(&self) -> isize {
+        self.double_area() / 2
+    }
+
+    /// The length of the boundary counted in unit grid steps (Manhattan length).
+    pub fn perimeter(&self) -> isize {
+        self.edges()
+            .map(|(a, b)| isize::try_from(a.0.abs_diff(b.0) + a.1.abs_diff(b.1)).unwrap())
+            .sum()
+    }
+
+    /// The number of lattice points lying on the boundary.
+    pub fn boundary_points(&self) -> isize {
+        self.edges().map(|(a, b)| gcd(b.0 - a.0, b.1 - a.1)).sum()
+    }
+
+    /// The number of lattice points strictly inside, using Pick's theorem.
+    pub fn interior_points(&self) -> isize {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// The number of unit cells covered when the boundary is a trench one cell
+    /// wide, i.e. interior plus boundary lattice points.
+    pub fn covered_cells(&self) -> isize {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        match self.signed_double_area().signum() {
+            1 => Orientation::CounterClockwise,
+            -1 => Orientation::Clockwise,
+            _ => Orientation::Degenerate,
+        }
+    }
+
+    pub fn locate(&self, point: &Coordinate<isize>) -> Location {
+        let Coordinate(px, py) = *point;
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let cross = (b.0 - a.0) * (py - a.1) - (b.1 - a.1) * (px - a.0);
+            if cross == 0
+                && (a.0.min(b.0)..=a.0.max(b.0)).contains(&px)
+                && (a.1.min(b.1)..=a.1.max(b.1)).contains(&py)
+            {
+                return Location::Boundary;
+            }
+            if (a.1 > py) != (b.1 > py) {
+                // x-coordinate of the edge at height py, compared without division
+                let lhs = (px - a.0) * (b.1 - a.1);
+                let rhs = (b.0 - a.0) * (py - a.1);
+                if (b.1 > a.1 && lhs < rhs) || (b.1 < a.1 && lhs > rhs) {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    pub fn contains(&self, point: &Coordinate<isize>) -> bool {
+        self.locate(point) != Location::Outside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Vec<Coordinate<isize>> {
+        vec![
+            Coordinate(0, 0),
+            Coordinate(4, 0),
+            Coordinate(4, 4),
+            Coordinate(0, 4),
+        ]
+    }
+
+    #[test]
+    fn test_measurements() {
+        let polygon = Polygon::from(&square());
+        assert_eq!(16, polygon.area());
+        assert_eq!(16, polygon.perimeter());
+        assert_eq!(16, polygon.boundary_points());
+        assert_eq!(9, polygon.interior_points());
+        assert_eq!(25, polygon.covered_cells());
+    }
+
+    #[test]
+    fn test_closed_and_unclosed_agree() {
+        let mut closed = square();
+        closed.push(Coordinate(0, 0));
+        let closed = Polygon::from(&closed);
+        let unclosed = Polygon::from(&square());
+        assert_eq!(unclosed.area(), closed.area());
+        assert_eq!(unclosed.perimeter(), closed.perimeter());
+        assert_eq!(unclosed.interior_points(), closed.interior_points());
+    }
+
+    #[test]
+    fn test_diagonal_edges() {
+        let triangle = Polygon::from(&vec![Coordinate(0, 0), Coordinate(4, 0), Coordinate(0, 4)]);
+        assert_eq!(16, triangle.double_area());
+        assert_eq!(12, triangle.boundary_points());
+        assert_eq!(3, triangle.interior_points());
+    }
+
+    #[test]
+    fn test_orientation() {
+        let mut points = square();
+        assert_eq!(
+            Orientation::CounterClockwise,
+            Polygon::from(&points).orientation()
+        );
+        points.reverse();
+        assert_eq!(Orientation::Clockwise, Polygon::from(&points).orientation());
+        assert_eq!(
+            Orientation::Degenerate,
+            Polygon::from(&vec![Coordinate(0, 0), Coordinate(3, 0)]).orientation()
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let polygon = Polygon::from(&square());
+        assert_eq!(Location::Inside, polygon.locate(&Coordinate(2, 2)));
+        assert_eq!(Location::Boundary, polygon.locate(&Coordinate(0, 2)));
+        assert_eq!(Location::Boundary, polygon.locate(&Coordinate(4, 4)));
+        assert_eq!(Location::Outside, polygon.locate(&Coordinate(5, 2)));
+        assert_eq!(Location::Outside, polygon.locate(&Coordinate(2, -1)));
+        assert!(polygon.contains(&Coordinate(4, 1)));
+        assert!(!polygon.contains(&Coordinate(-1, 0)));
+    }
+}
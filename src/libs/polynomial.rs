@@ -0,0 +1,191 @@
+use num::{One, Zero};
+
+use super::rational::{rational, Rational};
+
+/// Rows of repeated forward differences, starting with `values` itself and
+/// ending with the first row that is all zeros (or has a single entry).
+pub fn difference_table(values: &[i64]) -> Vec<Vec<i64>> {
+    let mut table = vec![values.to_vec()];
+    loop {
+        let last = table.last().unwrap();
+        if last.len() <= 1 || last.iter().all(|&v| v == 0) {
+            return table;
+        }
+        let next = last.windows(2).map(|w| w[1] - w[0]).collect();
+        table.push(next);
+    }
+}
+
+/// The degree of the polynomial generating equally spaced `values`, or `None`
+/// when there are too few samples for the differences to reach zero.
+pub fn degree(values: &[i64]) -> Option<usize> {
+    let table = difference_table(values);
+    if table.last().unwrap().iter().all(|&v| v == 0) {
+        Some(table.len().saturating_sub(2))
+    } else {
+        None
+    }
+}
+
+/// The lowest-degree polynomial through `values`, sampled at `x = 0, 1, 2, ..`.
+pub fn fit(values: &[i64]) -> Polynomial {
+    let points: Vec<(i64, i64)> = (0..).zip(values.iter().copied()).collect();
+    Polynomial::newton(&points)
+}
+
+/// Evaluates the polynomial fitted to `values` at any `index`, including
+/// negative ones.
+pub fn extrapolate(values: &[i64], index: i64) -> Rational {
+    fit(values).evaluate(&rational(index))
+}
+
+/// A polynomial with exact rational coefficients, lowest power first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polynomial(Vec<Rational>);
+
+impl Polynomial {
+    fn new(mut coefficients: Vec<Rational>) -> Self {
+        while coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+        Self(coefficients)
+    }
+
+    /// Adds `factor * basis` into `coefficients`, growing it as needed.
+    fn accumulate(coefficients: &mut Vec<Rational>, basis: &[Rational], factor: &Rational) {
+        if coefficients.len() < basis.len() {
+            coefficients.resize(basis.len(), Rational::zero());
+        }
+        for (c, b) in coefficients.iter_mut().zip(basis) {
+            *c += b * factor;
+        }
+    }
+
+    /// Multiplies `basis` by `(x - root)`.
+    fn multiply_root(basis: &[Rational], root: &Rational) -> Vec<Rational> {
+        let mut product = vec![Rational::zero(); basis.len() + 1];
+        for (i, b) in basis.iter().enumerate() {
+            product[i + 1] += b;
+            product[i] -= b * root;
+        }
+        product
+    }
+
+    /// Interpolates through `points` using Lagrange basis polynomials.
+    pub fn lagrange(points: &[(i64, i64)]) -> Self {
+        let mut coefficients = vec![];
+        for (i, &(xi, yi)) in points.iter().enumerate() {
+            let mut basis = vec![Rational::one()];
+            let mut denominator = Rational::one();
+            for (j, &(xj, _)) in points.iter().enumerate() {
+                if i != j {
+                    basis = Self::multiply_root(&basis, &rational(xj));
+                    denominator *= rational(xi - xj);
+                }
+            }
+            Self::accumulate(&mut coefficients, &basis, &(rational(yi) / denominator));
+        }
+        Self::new(coefficients)
+    }
+
+    /// Interpolates through `points` using Newton's divided differences.
+    pub fn newton(points: &[(i64, i64)]) -> Self {
+        let xs: Vec<Rational> = points.iter().map(|&(x, _)| rational(x)).collect();
+        let mut divided: Vec<Rational> = points.iter().map(|&(_, y)| rational(y)).collect();
+        for level in 1..points.len() {
+            for i in (level..points.len()).rev() {
+                divided[i] = (&divided[i] - &divided[i - 1]) / (&xs[i] - &xs[i - level]);
+            }
+        }
+
+        let mut coefficients = vec![];
+        let mut basis = vec![Rational::one()];
+        for (c, x) in divided.iter().zip(&xs) {
+            Self::accumulate(&mut coefficients, &basis, c);
+            basis = Self::multiply_root(&basis, x);
+        }
+        Self::new(coefficients)
+    }
+
+    pub fn coefficients(&self) -> &[Rational] {
+        &self.0
+    }
+
+    /// The degree of the polynomial; the zero polynomial has degree 0.
+    pub fn degree(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    pub fn evaluate(&self, x: &Rational) -> Rational {
+        self.0
+            .iter()
+            .rev()
+            .fold(Rational::zero(), |acc, c| acc * x + c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::ToPrimitive;
+
+    use super::*;
+    use crate::libs::rational::fraction;
+
+    fn integer(value: &Rational) -> i64 {
+        assert!(value.is_integer(), "{value} is not an integer");
+        value.to_integer().to_i64().unwrap()
+    }
+
+    #[test]
+    fn test_difference_table() {
+        assert_eq!(
+            vec![
+                vec![1, 3, 6, 10, 15, 21],
+                vec![2, 3, 4, 5, 6],
+                vec![1, 1, 1, 1],
+                vec![0, 0, 0],
+            ],
+            difference_table(&[1, 3, 6, 10, 15, 21])
+        );
+    }
+
+    #[test]
+    fn test_degree() {
+        assert_eq!(Some(0), degree(&[0, 0, 0]));
+        assert_eq!(Some(0), degree(&[7, 7, 7]));
+        assert_eq!(Some(1), degree(&[0, 3, 6, 9]));
+        assert_eq!(Some(2), degree(&[1, 3, 6, 10, 15]));
+        assert_eq!(None, degree(&[1, 2, 4]));
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        assert_eq!(18, integer(&extrapolate(&[0, 3, 6, 9, 12, 15], 6)));
+        assert_eq!(28, integer(&extrapolate(&[1, 3, 6, 10, 15, 21], 6)));
+        assert_eq!(5, integer(&extrapolate(&[10, 13, 16, 21, 30, 45], -1)));
+        assert_eq!(5050, integer(&extrapolate(&[0, 1, 3, 6], 100)));
+    }
+
+    #[test]
+    fn test_lagrange_matches_newton() {
+        let points = [(-2, 7), (1, -3), (3, 12), (4, 0), (9, 5)];
+        let lagrange = Polynomial::lagrange(&points);
+        let newton = Polynomial::newton(&points);
+        assert_eq!(lagrange, newton);
+        assert_eq!(4, newton.degree());
+        for (x, y) in points {
+            assert_eq!(rational(y), newton.evaluate(&rational(x)));
+        }
+    }
+
+    #[test]
+    fn test_rational_coefficients() {
+        // x * (x + 1) / 2
+        let triangular = Polynomial::newton(&[(0, 0), (1, 1), (2, 3)]);
+        let half = fraction(1, 2);
+        assert_eq!(
+            &[Rational::zero(), half.clone(), half],
+            triangular.coefficients()
+        );
+    }
+}
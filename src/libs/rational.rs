@@ -0,0 +1,99 @@
+use num::{BigInt, BigRational, Zero};
+
+/// An arbitrary precision fraction; arithmetic never truncates or overflows.
+pub type Rational = BigRational;
+
+pub fn rational(value: impl Into<BigInt>) -> Rational {
+    Rational::from_integer(value.into())
+}
+
+pub fn fraction(numerator: impl Into<BigInt>, denominator: impl Into<BigInt>) -> Rational {
+    Rational::new(numerator.into(), denominator.into())
+}
+
+/// Solves `a * x = b` exactly using Gaussian elimination.
+///
+/// Returns `None` when the system has no unique solution.
+pub fn solve(mut a: Vec<Vec<Rational>>, mut b: Vec<Rational>) -> Option<Vec<Rational>> {
+    let n = b.len();
+    assert!(
+        a.len() == n && a.iter().all(|row| row.len() == n),
+        "expected a square {n}x{n} system"
+    );
+
+    for column in 0..n {
+        let pivot = (column..n).find(|&row| !a[row][column].is_zero())?;
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+
+        let pivot_row = a[column].clone();
+        let pivot_value = b[column].clone();
+        for row in (0..n).filter(|&row| row != column) {
+            if !a[row][column].is_zero() {
+                let factor = &a[row][column] / &pivot_row[column];
+                for (cell, pivot) in a[row].iter_mut().zip(&pivot_row).skip(column) {
+                    *cell -= &factor * pivot;
+                }
+                b[row] -= &factor * &pivot_value;
+            }
+        }
+    }
+
+    Some(
+        b.into_iter()
+            .zip(a)
+            .enumerate()
+            .map(|(i, (b, row))| b / &row[i])
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&[i64]]) -> Vec<Vec<Rational>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&v| rational(v)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_solve() {
+        let a = matrix(&[&[2, 1, -1], &[-3, -1, 2], &[-2, 1, 2]]);
+        let b = vec![rational(8), rational(-11), rational(-3)];
+        assert_eq!(
+            Some(vec![rational(2), rational(3), rational(-1)]),
+            solve(a, b)
+        );
+    }
+
+    #[test]
+    fn test_solve_fractional() {
+        let a = matrix(&[&[3, 0], &[0, 2]]);
+        let b = vec![rational(1), rational(1)];
+        assert_eq!(Some(vec![fraction(1, 3), fraction(1, 2)]), solve(a, b));
+    }
+
+    #[test]
+    fn test_solve_needs_pivoting() {
+        let a = matrix(&[&[0, 1], &[1, 0]]);
+        let b = vec![rational(5), rational(7)];
+        assert_eq!(Some(vec![rational(7), rational(5)]), solve(a, b));
+    }
+
+    #[test]
+    fn test_solve_singular() {
+        let a = matrix(&[&[1, 2], &[2, 4]]);
+        let b = vec![rational(1), rational(2)];
+        assert_eq!(None, solve(a, b));
+    }
+
+    #[test]
+    fn test_no_overflow() {
+        let big = rational(i128::MAX);
+        let a = vec![vec![big.clone()]];
+        let b = vec![&big * &big];
+        assert_eq!(Some(vec![big]), solve(a, b));
+    }
+}
@@ -0,0 +1,211 @@
+use super::bits::BitGrid;
+use super::{Coordinate, Direction, Grid, DOWN, LEFT, OFFSETS, RIGHT, UP};
+
+fn neighbours<T>(
+    grid: &Grid<T>,
+    coordinate: Coordinate<usize>,
+) -> impl Iterator<Item = Coordinate<usize>> + '_ {
+    OFFSETS.iter().filter_map(move |&direction| {
+        let Coordinate(x, y) = coordinate;
+        match direction {
+            UP if y > 0 => Some(Coordinate(x, y - 1)),
+            RIGHT if x + 1 < grid.width => Some(Coordinate(x + 1, y)),
+            DOWN if y + 1 < grid.height => Some(Coordinate(x, y + 1)),
+            LEFT if x > 0 => Some(Coordinate(x - 1, y)),
+            _ => None,
+        }
+    })
+}
+
+/// All cells reachable from `seed` through 4-connected passable cells.
+pub fn flood_fill<T>(
+    grid: &Grid<T>,
+    seed: Coordinate<usize>,
+    passable: impl Fn(&T) -> bool,
+) -> BitGrid {
+    let mut filled = BitGrid::new(grid.width, grid.height);
+    if !passable(&grid[&seed]) {
+        return filled;
+    }
+
+    filled.insert(&seed);
+    let mut todo = vec![seed];
+    while let Some(current) = todo.pop() {
+        for next in neighbours(grid, current) {
+            if !filled.contains(&next) && passable(&grid[&next]) {
+                filled.insert(&next);
+                todo.push(next);
+            }
+        }
+    }
+    filled
+}
+
+/// The 4-connected regions of passable cells, numbered in reading order.
+pub struct Regions {
+    pub labels: Grid<Option<usize>>,
+    pub sizes: Vec<usize>,
+    /// For every region, whether it reaches the edge of the grid.
+    edges: Vec<bool>,
+}
+
+impl Regions {
+    pub fn region(&self, coordinate: &Coordinate<usize>) -> Option<usize> {
+        self.labels[coordinate]
+    }
+
+    /// The number of regions.
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Whether the region reaches the edge of the grid, i.e. is not enclosed.
+    pub fn touches_edge(&self, region: usize) -> bool {
+        self.edges[region]
+    }
+}
+
+pub fn label<T>(grid: &Grid<T>, passable: impl Fn(&T) -> bool) -> Regions {
+    let mut labels = Grid::new(grid.width, grid.height, None);
+    let mut sizes = vec![];
+    let mut edges = vec![];
+    let on_edge = |Coordinate(x, y): Coordinate<usize>| {
+        x == 0 || y == 0 || x == grid.width - 1 || y == grid.height - 1
+    };
+
+    for i in 0..grid.points.len() {
+        let seed = grid.coord(i);
+        if labels[&seed].is_some() || !passable(&grid[&seed]) {
+            continue;
+        }
+        let region = flood_fill(grid, seed, &passable);
+        region.iter().for_each(|c| labels[&c] = Some(sizes.len()));
+        sizes.push(region.count_ones());
+        edges.push(region.iter().any(on_edge));
+    }
+
+    Regions {
+        labels,
+        sizes,
+        edges,
+    }
+}
+
+/// Doubles the resolution of `grid` so that regions can squeeze between
+/// walls that touch without being connected.
+///
+/// Cell `(x, y)` ends up at `(2x, 2y)`; the cells in between are walls only
+/// when `connected` says the walls on either side join in that direction.
+/// The result holds `true` for walls.
+pub fn upscale<T>(
+    grid: &Grid<T>,
+    wall: impl Fn(&T) -> bool,
+    connected: impl Fn(Coordinate<usize>, Direction) -> bool,
+) -> Grid<bool> {
+    let mut upscaled = Grid::new(grid.width * 2, grid.height * 2, false);
+    for i in 0..grid.points.len() {
+        let Coordinate(x, y) = grid.coord(i);
+        upscaled[&Coordinate(2 * x, 2 * y)] = wall(&grid.points[i]);
+        if x + 1 < grid.width {
+            upscaled[&Coordinate(2 * x + 1, 2 * y)] = connected(Coordinate(x, y), RIGHT);
+        }
+        if y + 1 < grid.height {
+            upscaled[&Coordinate(2 * x, 2 * y + 1)] = connected(Coordinate(x, y), DOWN);
+        }
+    }
+    upscaled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = Grid::from(
+            "..#..
+..#..
+###..
+.....",
+        );
+        let filled = flood_fill(&grid, Coordinate(0, 0), |&c| c == b'.');
+        assert_eq!(4, filled.count_ones());
+        let filled = flood_fill(&grid, Coordinate(4, 0), |&c| c == b'.');
+        assert_eq!(11, filled.count_ones());
+        let filled = flood_fill(&grid, Coordinate(2, 0), |&c| c == b'.');
+        assert!(filled.is_empty());
+    }
+
+    #[test]
+    fn test_label() {
+        let grid = Grid::from(
+            ".#.
+##.
+.#.",
+        );
+        let regions = label(&grid, |&c| c == b'.');
+        assert_eq!(3, regions.count());
+        assert_eq!(vec![1, 3, 1], regions.sizes);
+        assert_eq!(Some(0), regions.region(&Coordinate(0, 0)));
+        assert_eq!(Some(1), regions.region(&Coordinate(2, 2)));
+        assert_eq!(Some(2), regions.region(&Coordinate(0, 2)));
+        assert_eq!(None, regions.region(&Coordinate(1, 1)));
+    }
+
+    #[test]
+    fn test_touches_edge() {
+        let grid = Grid::from(
+            ".....
+.###.
+.#.#.
+.###.
+.....",
+        );
+        let regions = label(&grid, |&c| c == b'.');
+        assert!(regions.touches_edge(0));
+        assert!(!regions.touches_edge(1));
+        assert_eq!(vec![16, 1], regions.sizes);
+    }
+
+    #[test]
+    fn test_squeeze_between_pipes() {
+        let grid = Grid::from(
+            "..........
+.F------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........",
+        );
+        let upscaled = upscale(
+            &grid,
+            |&c| c != b'.',
+            |Coordinate(x, y), direction| {
+                let from = grid[&Coordinate(x, y)];
+                match direction {
+                    RIGHT => {
+                        b"-LF".contains(&from) && b"-J7".contains(&grid[&Coordinate(x + 1, y)])
+                    }
+                    DOWN => b"|7F".contains(&from) && b"|LJ".contains(&grid[&Coordinate(x, y + 1)]),
+                    _ => false,
+                }
+            },
+        );
+        let outside = flood_fill(&upscaled, Coordinate(0, 0), |&wall| !wall);
+        let enclosed = (0..grid.points.len())
+            .map(|i| grid.coord(i))
+            .filter(|&c| grid[&c] == b'.')
+            .filter(|&Coordinate(x, y)| !outside.contains(&Coordinate(2 * x, 2 * y)))
+            .count();
+        assert_eq!(4, enclosed);
+
+        // Without upscaling there is no way to squeeze between the two `||`
+        // pipes, so the cells inside the inner loop look enclosed too.
+        let regions = label(&grid, |&c| c == b'.');
+        assert_eq!(vec![34, 8, 2, 2], regions.sizes);
+        assert!(!regions.touches_edge(1));
+    }
+}
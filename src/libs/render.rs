@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{Coordinate, Grid};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const WHITE: Color = Color(255, 255, 255);
+    pub const RED: Color = Color(220, 50, 47);
+    pub const GREEN: Color = Color(133, 153, 0);
+    pub const BLUE: Color = Color(38, 139, 210);
+    pub const YELLOW: Color = Color(181, 137, 0);
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// How a single cell is drawn: a glyph for terminal output plus optional colours.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Style {
+    pub glyph: char,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Style {
+    pub fn plain(glyph: char) -> Self {
+        Self {
+            glyph,
+            foreground: None,
+            background: None,
+        }
+    }
+
+    /// The colour a cell gets in image output, where there is no glyph to show.
+    fn fill(&self) -> Color {
+        self.background.or(self.foreground).unwrap_or(Color::WHITE)
+    }
+}
+
+type StyleFn<'a, T> = Box<dyn Fn(Coordinate<usize>, &T) -> Style + 'a>;
+
+/// Renders a grid to a terminal or an image, with highlighted cells and paths
+/// drawn on top of the per-cell styles.
+pub struct Renderer<'a, T> {
+    grid: &'a Grid<T>,
+    style: StyleFn<'a, T>,
+    highlights: HashMap<Coordinate<usize>, Color>,
+    paths: Vec<(Vec<Coordinate<usize>>, Color)>,
+    path_cells: HashMap<Coordinate<usize>, Color>,
+}
+
+impl<'a, T> Renderer<'a, T> {
+    pub fn new(grid: &'a Grid<T>, style: impl Fn(Coordinate<usize>, &T) -> Style + 'a) -> Self {
+        Self {
+            grid,
+            style: Box::new(style),
+            highlights: HashMap::new(),
+            paths: vec![],
+            path_cells: HashMap::new(),
+        }
+    }
+
+    pub fn highlight(
+        mut self,
+        cells: impl IntoIterator<Item = Coordinate<usize>>,
+        color: Color,
+    ) -> Self {
+        self.highlights
+            .extend(cells.into_iter().map(|cell| (cell, color)));
+        self
+    }
+
+    /// Draws a path; in terminal and PPM output its cells are highlighted,
+    /// in SVG output it becomes a line through the cell centres.
+    pub fn path(mut self, path: &[Coordinate<usize>], color: Color) -> Self {
+        self.path_cells
+            .extend(path.iter().map(|&cell| (cell, color)));
+        self.paths.push((path.to_vec(), color));
+        self
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (Coordinate<usize>, Style)> + '_ {
+        self.grid.points.iter().enumerate().map(|(i, t)| {
+            let coordinate = self.grid.coord(i);
+            (coordinate, (self.style)(coordinate, t))
+        })
+    }
+
+    fn overlay(&self, coordinate: &Coordinate<usize>) -> Option<Color> {
+        self.highlights
+            .get(coordinate)
+            .or_else(|| self.path_cells.get(coordinate))
+            .copied()
+    }
+
+    pub fn ansi(&self) -> String {
+        let mut out = String::new();
+        for (coordinate, style) in self.cells() {
+            let background = self.overlay(&coordinate).or(style.background);
+            if let Some(Color(r, g, b)) = style.foreground {
+                write!(out, "\x1b[38;2;{r};{g};{b}m").unwrap();
+            }
+            if let Some(Color(r, g, b)) = background {
+                write!(out, "\x1b[48;2;{r};{g};{b}m").unwrap();
+            }
+            out.push(style.glyph);
+            if style.foreground.is_some() || background.is_some() {
+                out.push_str("\x1b[0m");
+            }
+            if coordinate.0 == self.grid.width - 1 {
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// A plain-text (P3) PPM image with every cell drawn as `scale` x `scale` pixels.
+    pub fn ppm(&self, scale: usize) -> String {
+        let colors: Vec<Color> = self
+            .cells()
+            .map(|(coordinate, style)| self.overlay(&coordinate).unwrap_or(style.fill()))
+            .collect();
+        let mut out = format!(
+            "P3\n{} {}\n255\n",
+            self.grid.width * scale,
+            self.grid.height * scale
+        );
+        for row in colors.chunks(self.grid.width) {
+            let line = row
+                .iter()
+                .flat_map(|color| std::iter::repeat_n(color, scale))
+                .map(|Color(r, g, b)| format!("{r} {g} {b}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            for _ in 0..scale {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn svg(&self, cell_size: usize) -> String {
+        let (width, height) = (self.grid.width * cell_size, self.grid.height * cell_size);
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        for (coordinate, style) in self.cells() {
+            let fill = self
+                .highlights
+                .get(&coordinate)
+                .copied()
+                .unwrap_or(style.fill());
+            writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{cell_size}\" height=\"{cell_size}\" fill=\"{}\"/>",
+                coordinate.0 * cell_size,
+                coordinate.1 * cell_size,
+                fill.hex()
+            )
+            .unwrap();
+        }
+        for (path, color) in &self.paths {
+            let points = path
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{},{}",
+                        c.0 * cell_size + cell_size / 2,
+                        c.1 * cell_size + cell_size / 2
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(
+                out,
+                "<polyline points=\"{points}\" fill=\"none\" stroke=\"{}\"/>",
+                color.hex()
+            )
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        Grid::from("#.\n.#")
+    }
+
+    fn style(_: Coordinate<usize>, c: &u8) -> Style {
+        match c {
+            b'#' => Style {
+                glyph: '#',
+                foreground: None,
+                background: Some(Color::BLACK),
+            },
+            _ => Style::plain('.'),
+        }
+    }
+
+    #[test]
+    fn test_ansi() {
+        let grid = grid();
+        assert_eq!(
+            "\x1b[48;2;0;0;0m#\x1b[0m.\n.\x1b[48;2;0;0;0m#\x1b[0m\n",
+            Renderer::new(&grid, style).ansi()
+        );
+    }
+
+    #[test]
+    fn test_ansi_highlight() {
+        let grid = grid();
+        assert_eq!(
+            "\x1b[48;2;0;0;0m#\x1b[0m\x1b[48;2;220;50;47m.\x1b[0m\n.\x1b[48;2;0;0;0m#\x1b[0m\n",
+            Renderer::new(&grid, style)
+                .highlight([Coordinate(1, 0)], Color::RED)
+                .ansi()
+        );
+    }
+
+    #[test]
+    fn test_ppm() {
+        let grid = grid();
+        assert_eq!(
+            "P3\n2 2\n255\n0 0 0 255 255 255\n255 255 255 0 0 0\n",
+            Renderer::new(&grid, style).ppm(1)
+        );
+        assert_eq!(
+            "P3\n4 4\n255\n\
+            0 0 0 0 0 0 38 139 210 38 139 210\n\
+            0 0 0 0 0 0 38 139 210 38 139 210\n\
+            255 255 255 255 255 255 0 0 0 0 0 0\n\
+            255 255 255 255 255 255 0 0 0 0 0 0\n",
+            Renderer::new(&grid, style)
+                .path(&[Coordinate(1, 0)], Color::BLUE)
+                .ppm(2)
+        );
+    }
+
+    #[test]
+    fn test_svg() {
+        let grid = grid();
+        let svg = Renderer::new(&grid, style)
+            .highlight([Coordinate(0, 1)], Color::GREEN)
+            .path(&[Coordinate(0, 0), Coordinate(1, 0)], Color::RED)
+            .svg(10);
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"20\" viewBox=\"0 0 20 20\">\n\
+            <rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" fill=\"#000000\"/>\n\
+            <rect x=\"10\" y=\"0\" width=\"10\" height=\"10\" fill=\"#ffffff\"/>\n\
+            <rect x=\"0\" y=\"10\" width=\"10\" height=\"10\" fill=\"#859900\"/>\n\
+            <rect x=\"10\" y=\"10\" width=\"10\" height=\"10\" fill=\"#000000\"/>\n\
+            <polyline points=\"5,5 15,5\" fill=\"none\" stroke=\"#dc322f\"/>\n\
+            </svg>\n",
+            svg
+        );
+    }
+}
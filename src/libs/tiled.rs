@@ -0,0 +1,107 @@
+use std::ops::Index;
+
+use super::{Coordinate, Grid};
+
+/// An infinite plane covered by copies of `grid`, addressed with signed
+/// coordinates. Copy `(0, 0)` is the grid itself; copy `(1, -1)` lies one
+/// grid width to the right and one grid height up.
+pub struct Tiled<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<'a, T> Tiled<'a, T> {
+    pub fn new(grid: &'a Grid<T>) -> Self {
+        Self { grid }
+    }
+
+    pub fn width(&self) -> isize {
+        isize::try_from(self.grid.width).unwrap()
+    }
+
+    pub fn height(&self) -> isize {
+        isize::try_from(self.grid.height).unwrap()
+    }
+
+    /// The copy that `coordinate` falls in.
+    pub fn tile(&self, coordinate: &Coordinate<isize>) -> Coordinate<isize> {
+        Coordinate(
+            coordinate.0.div_euclid(self.width()),
+            coordinate.1.div_euclid(self.height()),
+        )
+    }
+
+    /// The matching coordinate within the base grid.
+    pub fn wrap(&self, coordinate: &Coordinate<isize>) -> Coordinate<usize> {
+        Coordinate(
+            usize::try_from(coordinate.0.rem_euclid(self.width())).unwrap(),
+            usize::try_from(coordinate.1.rem_euclid(self.height())).unwrap(),
+        )
+    }
+
+    /// The coordinate of `local` within copy `tile`; the inverse of `tile`
+    /// and `wrap`.
+    pub fn in_tile(
+        &self,
+        tile: &Coordinate<isize>,
+        local: &Coordinate<usize>,
+    ) -> Coordinate<isize> {
+        Coordinate(
+            tile.0 * self.width() + isize::try_from(local.0).unwrap(),
+            tile.1 * self.height() + isize::try_from(local.1).unwrap(),
+        )
+    }
+}
+
+impl<'a, T> Index<&Coordinate<isize>> for Tiled<'a, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, coordinate: &Coordinate<isize>) -> &Self::Output {
+        &self.grid[&self.wrap(coordinate)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        let grid = Grid::from(
+            "ab
+cd
+ef",
+        );
+        let tiled = Tiled::new(&grid);
+        assert_eq!(b'a', tiled[&Coordinate(0, 0)]);
+        assert_eq!(b'd', tiled[&Coordinate(1, 1)]);
+        assert_eq!(b'b', tiled[&Coordinate(-1, 0)]);
+        assert_eq!(b'e', tiled[&Coordinate(0, -1)]);
+        assert_eq!(b'c', tiled[&Coordinate(4, 7)]);
+        assert_eq!(b'f', tiled[&Coordinate(-3, -4)]);
+    }
+
+    #[test]
+    fn test_tile() {
+        let grid = Grid::new(3, 2, ());
+        let tiled = Tiled::new(&grid);
+        assert_eq!(Coordinate(0, 0), tiled.tile(&Coordinate(2, 1)));
+        assert_eq!(Coordinate(1, 0), tiled.tile(&Coordinate(3, 1)));
+        assert_eq!(Coordinate(-1, -1), tiled.tile(&Coordinate(-1, -1)));
+        assert_eq!(Coordinate(-1, -2), tiled.tile(&Coordinate(-3, -3)));
+    }
+
+    #[test]
+    fn test_wrap_and_in_tile() {
+        let grid = Grid::new(3, 2, ());
+        let tiled = Tiled::new(&grid);
+        for y in -5..5 {
+            for x in -7..7 {
+                let coordinate = Coordinate(x, y);
+                let (tile, local) = (tiled.tile(&coordinate), tiled.wrap(&coordinate));
+                assert!(local.0 < 3 && local.1 < 2);
+                assert_eq!(coordinate, tiled.in_tile(&tile, &local));
+            }
+        }
+    }
+}
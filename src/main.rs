@@ -1,6 +1,8 @@
-use std::{env, time::Instant};
+use std::{env, fs, time::Instant};
 
-use crate::problem::Solver;
+use adventofcode_2023::libs;
+
+use crate::problem::{Image, Solver};
 
 mod day01;
 mod day02;
@@ -27,13 +29,18 @@ mod day22;
 mod day23;
 mod day24;
 mod day25;
-mod libs;
 mod problem;
 
 fn main() {
     let now = Instant::now();
     let args: Vec<String> = env::args().collect();
     let only_day: Option<i32> = args.get(1).map(|a| a.parse().unwrap());
+    let debug = args.get(2).is_some_and(|a| a == "debug");
+    let image = match args.get(2).map(String::as_str) {
+        Some("ppm") => Some(Image::Ppm),
+        Some("svg") => Some(Image::Svg),
+        _ => None,
+    };
 
     for day in (1..=25).filter(|d| only_day.is_none() || only_day.unwrap() == *d) {
         let (d, inp): (&dyn Solver, &str) = match day {
@@ -79,6 +86,19 @@ fn main() {
             d.pt2(inp),
             now.elapsed()
         );
+
+        if debug {
+            if let Some(output) = d.debug(inp) {
+                print!("{output}");
+            }
+        }
+        if let Some(format) = image {
+            let path = args.get(3).expect("expected a path to write the image to");
+            match d.image(inp, format) {
+                Some(image) => fs::write(path, image).unwrap(),
+                None => println!("day{day:02} has no image"),
+            }
+        }
     }
     println!("total: {:.2?}", now.elapsed());
 }
@@ -1,4 +1,21 @@
+/// The image formats a solver can be drawn in.
+#[derive(Clone, Copy, Debug)]
+pub enum Image {
+    Ppm,
+    Svg,
+}
+
 pub trait Solver {
     fn pt1(&self, inp: &str) -> String;
     fn pt2(&self, inp: &str) -> String;
+    /// Extra output for inspecting a solution, printed when the runner is
+    /// given `debug` after the day.
+    fn debug(&self, _inp: &str) -> Option<String> {
+        None
+    }
+    /// The solution drawn as an image, written to a file when the runner is
+    /// given `ppm` or `svg` and a path after the day.
+    fn image(&self, _inp: &str, _format: Image) -> Option<String> {
+        None
+    }
 }